@@ -0,0 +1,79 @@
+//! In this example, the CFT data of the critical S=1/2 AFH chain are 
+//! extracted from the lowest eigenvalues of the sectors of several 
+//! chain lengths. The chain is described by the SU(2)_1 CFT with 
+//! central charge 1 and velocity π/2. The lowest triplet and the 
+//! singlet with momentum π both have scaling dimension 1/2, but 
+//! a marginal operator gives them logarithmic corrections of 
+//! opposite sign, which almost cancel in the average over the four 
+//! states.
+use quantum_spin_chains::basis::SzSector;
+use quantum_spin_chains::cft::{get_cft_data, get_sector_energies, Velocity};
+use quantum_spin_chains::error::Error;
+use quantum_spin_chains::hamiltonians::{Hamiltonian, AFH};
+use quantum_spin_chains::model::Model;
+use quantum_spin_chains::symmetries::{Momentum, Parity};
+use std::f32::consts::PI;
+
+fn main() -> Result<(), Error> {
+    // Defining parameters of the chains and the calculation
+    let base = 2; // corresponds to S=1/2
+    let lengths = [8, 12, 16]; // chain lengths, multiples of 4
+    let s = 1.0; // refers to s*H_AFH + (1-s)*H_triv=H_AFH
+    let iterations = 3000; // iterations of power iteration
+    let hamiltonian = Hamiltonian::<AFH>::new(s);
+
+    let mut energies_per_site = Vec::new();
+    let mut triplet_gaps = Vec::new();
+    let mut singlet_gaps = Vec::new();
+
+    for length in lengths {
+        // The ground state is symmetric and the singlet is odd under translation
+        let singlet_model = Model::new(base, length, SzSector::new(0))?;
+        let triplet_model = Model::new(base, length, SzSector::new(1))?;
+        let singlet_energies = get_sector_energies(&hamiltonian, &singlet_model, iterations)?;
+        let triplet_energies = get_sector_energies(&hamiltonian, &triplet_model, iterations)?;
+        let get_energy = |time_reversal, reflection, translation| singlet_energies
+            .iter()
+            .find(|sector_energy| {
+                sector_energy.time_reversal == time_reversal
+                    && sector_energy.reflection == reflection
+                    && sector_energy.translation == translation
+            })
+            .map(|sector_energy| sector_energy.energy)
+            .ok_or(Error::EmptySector);
+        let gs_energy = get_energy(Parity::Even, Parity::Even, Momentum::Zero)?;
+        let singlet_energy = get_energy(Parity::Even, Parity::Even, Momentum::Pi)?;
+        let triplet_energy = triplet_energies
+            .iter()
+            .map(|sector_energy| sector_energy.energy)
+            .fold(f32::INFINITY, f32::min);
+
+        println!(
+            "Length {}: energy per site {}, triplet gap {}, singlet gap {}",
+            length,
+            gs_energy / length as f32,
+            triplet_energy - gs_energy,
+            singlet_energy - gs_energy,
+        );
+        energies_per_site.push((length, gs_energy / length as f32));
+        triplet_gaps.push((length, triplet_energy - gs_energy));
+        singlet_gaps.push((length, singlet_energy - gs_energy));
+    }
+
+    // Extracting the CFT data with the known velocity
+    let cft_data = get_cft_data(
+        &energies_per_site,
+        &[triplet_gaps, singlet_gaps],
+        Velocity::Known(PI / 2.0),
+    )?;
+    let triplet_dimension = cft_data.scaling_dimensions[0];
+    let singlet_dimension = cft_data.scaling_dimensions[1];
+
+    println!("Energy per site: {} ± {}", cft_data.energy_per_site.value, cft_data.energy_per_site.uncertainty);
+    println!("Central charge: {} ± {}", cft_data.central_charge.value, cft_data.central_charge.uncertainty);
+    println!("Triplet scaling dimension: {} ± {}", triplet_dimension.value, triplet_dimension.uncertainty);
+    println!("Singlet scaling dimension: {} ± {}", singlet_dimension.value, singlet_dimension.uncertainty);
+    println!("Average scaling dimension: {}", (3.0 * triplet_dimension.value + singlet_dimension.value) / 4.0);
+
+    Ok(())
+}
\ No newline at end of file
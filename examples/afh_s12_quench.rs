@@ -0,0 +1,77 @@
+//! In this example, the S=1/2 chain of length 12 is prepared in the 
+//! Néel state |↑↓↑↓...⟩ and evolved with the AFH Hamiltonian. The 
+//! staggered magnetization decays from 1/2 and oscillates around 0, 
+//! while the norm and the energy are conserved. The Néel state is not 
+//! invariant under translation by one site, so no symmetries are used 
+//! except S^z conservation.
+use quantum_spin_chains::basis::SzSector;
+use quantum_spin_chains::error::Error;
+use quantum_spin_chains::evolution::{time_evolve, ComplexState};
+use quantum_spin_chains::hamiltonians::{Hamiltonian, AFH};
+use quantum_spin_chains::model::Model;
+use quantum_spin_chains::sector::Sector;
+
+fn main() -> Result<(), Error> {
+    // Defining parameters of the chain and the calculation
+    let base = 2; // corresponds to S=1/2
+    let length = 12; // chain length
+    let total_s_z = SzSector::new(0); // total quantum spin in the z-direction is 0
+    let s = 1.0; // refers to s*H_AFH + (1-s)*H_triv=H_AFH
+    let time_step = 0.1; // length of a time step
+    let steps = 50; // number of time steps
+    let krylov_dimension = 20; // dimension of the Krylov space of a time step
+
+    // Defining the Hamiltonian, the model and the sector
+    let hamiltonian = Hamiltonian::<AFH>::new(s);
+    let model = Model::new_plain(base, length, Some(total_s_z))?;
+    let sector = Sector::trivial(&model)?;
+
+    // The Néel state, where the even sites have digit 1 (up) 
+    // and the odd sites have digit 0 (down)
+    let site_states: Vec<Vec<f32>> = (0..length)
+        .map(|site| if site % 2 == 0 {vec![0.0, 1.0]} else {vec![1.0, 0.0]})
+        .collect();
+    let initial_state = ComplexState::from_real(sector.from_product_state(&site_states)?, &sector)?;
+
+    // The staggered magnetization per site
+    let staggered_magnetization = |state: &ComplexState, sector: &Sector| {
+        let probabilities = state.get_probabilities(sector)?;
+
+        Ok(probabilities
+            .iter()
+            .enumerate()
+            .map(|(value, probability)| {
+                let staggered_s_z: f32 = (0..length as u32)
+                    .map(|site| {
+                        let sigma = ((value >> site) & 1) as f32 - 0.5;
+                        if site % 2 == 0 {sigma} else {-sigma}
+                    })
+                    .sum();
+
+                probability * staggered_s_z / length as f32
+            })
+            .sum())
+    };
+
+    let (_final_state, evolution_points) = time_evolve(
+        &hamiltonian,
+        initial_state,
+        &sector,
+        time_step,
+        steps,
+        krylov_dimension,
+        &[&staggered_magnetization],
+    )?;
+
+    for evolution_point in evolution_points.iter() {
+        println!(
+            "t: {:.1}, staggered magnetization: {}, norm deviation: {}, energy deviation: {}",
+            evolution_point.time,
+            evolution_point.observables[0],
+            evolution_point.norm_deviation,
+            evolution_point.energy_deviation,
+        );
+    }
+
+    Ok(())
+}
\ No newline at end of file
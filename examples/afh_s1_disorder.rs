@@ -0,0 +1,97 @@
+//! In this example, the ground state energy of the S=1 AFH chain of 
+//! length 8 with a random field in the z-direction on every site is 
+//! calculated. The field breaks time-reversal symmetry, reflection 
+//! symmetry and translation symmetry, but the total quantum spin in 
+//! the z-direction is still conserved, so the model only uses the 
+//! subspace where it is 0.
+use quantum_spin_chains::basis::SzSector;
+use quantum_spin_chains::diagnostics::check_hamiltonian;
+use quantum_spin_chains::error::Error;
+use quantum_spin_chains::model::Model;
+use quantum_spin_chains::hamiltonians::{apply_matrix_elements, Hamiltonian, Implemented, MatrixElements, AFH};
+use quantum_spin_chains::sector::Sector;
+use quantum_spin_chains::states::State;
+
+/// The AFH Hamiltonian plus the sum of h_n*S^z_n.
+struct DisorderedAFH {
+    afh: Hamiltonian<AFH>,
+    fields: Vec<f32>,
+}
+
+impl MatrixElements for DisorderedAFH {
+    fn get_matrix_elements(
+        &self,
+        value: usize,
+        digits: &[u8],
+        model: &Model,
+        matrix_elements: &mut Vec<(usize, f32)>,
+    ) {
+        self.afh.get_matrix_elements(value, digits, model, matrix_elements);
+
+        let field_energy: f32 = self.fields
+            .iter()
+            .zip(digits.iter())
+            .map(|(field, digit)| field * (*digit as f32 - model.spin))
+            .sum();
+
+        matrix_elements.push((value, field_energy));
+    }
+}
+
+impl Implemented for DisorderedAFH {
+    fn apply(
+        &self,
+        input_state: &State,
+        output_state: &mut State,
+        sector: &Sector,
+    ) -> Result<(), Error> {
+        apply_matrix_elements(self, input_state, output_state, sector)
+    }
+
+    fn get_max_eigenenergy(&self, model: &Model) -> f32 {
+        let max_field_energy: f32 = self.fields
+            .iter()
+            .map(|field| field.abs() * model.spin)
+            .sum();
+
+        self.afh.get_max_eigenenergy(model) + max_field_energy
+    }
+}
+
+fn main() -> Result<(), Error> {
+    // Defining parameters of the chain and the calculation
+    let base = 3; // corresponds to S=1
+    let length = 8; // chain length
+    let total_s_z = SzSector::new(0); // total quantum spin in the z-direction is 0
+    let fields = vec![0.31, -0.12, 0.47, -0.38, 0.05, -0.26, 0.19, -0.44]; // random fields
+    let iterations = 2000; // iterations of power iteration
+
+    // Defining the Hamiltonian and the model without symmetries
+    // except the conservation of the total quantum spin in the 
+    // z-direction
+    let hamiltonian = DisorderedAFH {
+        afh: Hamiltonian::<AFH>::new(1.0),
+        fields,
+    };
+    let model = Model::new_plain(base, length, Some(total_s_z))?;
+    let sector = Sector::trivial(&model)?;
+
+    // Checking that the Hamiltonian has the symmetries of the model
+    let violations = check_hamiltonian(&hamiltonian, &model, 1e-5)?;
+    println!("Violated symmetries: {:?}", violations);
+
+    // Defining storage for lower eigenvalues and corresponding
+    // eigenvectors (not relevant here)
+    let lower_eigenpairs = Vec::new();
+
+    // Finding the ground state
+    let (_gs, gs_energy) = sector.find_eigenstate(
+        &hamiltonian,
+        iterations,
+        &lower_eigenpairs
+    )?;
+
+    println!("Ground state energy: {}", gs_energy);
+
+    Ok(())
+}
\ No newline at end of file
@@ -0,0 +1,41 @@
+//! In this example, the magnetization curve of the S=1 AFH chain of 
+//! length 10 is calculated from the lowest energies of the sectors of 
+//! total quantum spin in the z-direction. The magnetization vanishes 
+//! below the critical field, which is the spectral gap (the Haldane 
+//! plateau), and the chain is fully polarized above the saturation 
+//! field 4.
+use quantum_spin_chains::error::Error;
+use quantum_spin_chains::hamiltonians::{Hamiltonian, AFH};
+use quantum_spin_chains::magnetization::get_magnetization_curve;
+use quantum_spin_chains::model::ModelBuilder;
+use quantum_spin_chains::symmetries::Reflection;
+
+fn main() -> Result<(), Error> {
+    // Defining parameters of the chain and the calculation
+    let base = 3; // corresponds to S=1
+    let length = 10; // chain length
+    let s = 1.0; // refers to s*H_AFH + (1-s)*H_triv=H_AFH
+    let iterations = 500; // iterations of power iteration
+
+    // Defining the Hamiltonian and the symmetries used in every sector
+    let hamiltonian = Hamiltonian::<AFH>::new(s);
+    let model_builder = ModelBuilder::new(base, length)
+        .time_reversal(true)
+        .reflection(Some(Reflection::Bond))
+        .translation(true);
+
+    let magnetization_curve = get_magnetization_curve(&hamiltonian, model_builder, iterations)?;
+
+    for (s_z, energy) in magnetization_curve.energies.iter() {
+        println!("S^z = {}: energy {}", s_z, energy);
+    }
+
+    for step in magnetization_curve.steps.iter() {
+        println!("Field {}: magnetization per site {}", step.field, step.magnetization);
+    }
+
+    println!("Critical field: {:?}", magnetization_curve.get_critical_field());
+    println!("Saturation field: {:?}", magnetization_curve.get_saturation_field());
+
+    Ok(())
+}
\ No newline at end of file
@@ -0,0 +1,58 @@
+//! In this example, the ground state energy per site and the spectral 
+//! gap of the S=1 AFH chain are calculated for several chain lengths 
+//! and extrapolated to infinite length. The chain is gapped, so the 
+//! finite-size corrections decay exponentially, and both the fit to 
+//! a + b e^{-L/ξ} and the Shanks transformation apply. The 
+//! extrapolations approach the known values -1.40148 and 0.41048, 
+//! and improve when longer chains are added.
+use quantum_spin_chains::basis::SzSector;
+use quantum_spin_chains::error::Error;
+use quantum_spin_chains::hamiltonians::{Hamiltonian, AFH};
+use quantum_spin_chains::model::Model;
+use quantum_spin_chains::scaling::{extrapolate_shanks, fit_exponential, get_sequence};
+use quantum_spin_chains::sector::Sector;
+use quantum_spin_chains::symmetries::{Momentum, Parity};
+
+fn main() -> Result<(), Error> {
+    // Defining parameters of the chains and the calculation
+    let base = 3; // corresponds to S=1
+    let lengths = [6, 8, 10, 12]; // chain lengths
+    let total_s_z = SzSector::new(0); // total quantum spin in the z-direction is 0
+    let s = 1.0; // refers to s*H_AFH + (1-s)*H_triv=H_AFH
+    let iterations = 500; // iterations of power iteration
+    let hamiltonian = Hamiltonian::<AFH>::new(s);
+
+    // Finding the ground state energy and the spectral gap for every length
+    let mut gaps = Vec::new();
+    let energies_per_site = get_sequence(&lengths, |length| {
+        let model = Model::new(base, length, total_s_z)?;
+        let symmetric_sector = Sector::new(&model, Parity::Even, Parity::Even, Momentum::Zero)?;
+        let antisymmetric_sector = Sector::new(&model, Parity::Odd, Parity::Odd, Momentum::Pi)?;
+        let (_gs, gs_energy) = symmetric_sector.find_eigenstate(&hamiltonian, iterations, &[])?;
+        let (_fes, fes_energy) = antisymmetric_sector.find_eigenstate(&hamiltonian, iterations, &[])?;
+        println!("Length {}: energy per site {}, gap {}", length, gs_energy / length as f32, fes_energy - gs_energy);
+        gaps.push((length, fes_energy - gs_energy));
+
+        Ok(gs_energy / length as f32)
+    })?;
+
+    // Extrapolating to infinite length
+    let energy_fit = fit_exponential(&energies_per_site)?;
+    let gap_fit = fit_exponential(&gaps)?;
+    let gap_shanks = extrapolate_shanks(&gaps)?;
+
+    println!(
+        "Energy per site (fit): {} ± {}",
+        energy_fit.extrapolation.value,
+        energy_fit.extrapolation.uncertainty,
+    );
+    println!(
+        "Spectral gap (fit): {} ± {}, correlation length {}",
+        gap_fit.extrapolation.value,
+        gap_fit.extrapolation.uncertainty,
+        gap_fit.correlation_length,
+    );
+    println!("Spectral gap (Shanks): {} ± {}", gap_shanks.value, gap_shanks.uncertainty);
+
+    Ok(())
+}
\ No newline at end of file
@@ -0,0 +1,52 @@
+//! In this example, the dynamical structure factor S(π, ω) of the S=1 
+//! AFH chain of length 10 is calculated. The operator S^z_π maps the 
+//! symmetric ground state to the sector where all three symmetries 
+//! have eigenvalue -1, and almost all of its weight is in a single 
+//! peak at the spectral gap (the Haldane magnon).
+use quantum_spin_chains::basis::SzSector;
+use quantum_spin_chains::error::Error;
+use quantum_spin_chains::hamiltonians::{Hamiltonian, AFH};
+use quantum_spin_chains::model::Model;
+use quantum_spin_chains::sector::Sector;
+use quantum_spin_chains::structure_factor::{get_dynamical_structure_factor, SpinOperator};
+use quantum_spin_chains::symmetries::{Momentum, Parity};
+use std::f32::consts::PI;
+
+fn main() -> Result<(), Error> {
+    // Defining parameters of the chain and the calculation
+    let base = 3; // corresponds to S=1
+    let length = 10; // chain length
+    let total_s_z = SzSector::new(0); // total quantum spin in the z-direction is 0
+    let s = 1.0; // refers to s*H_AFH + (1-s)*H_triv=H_AFH
+    let iterations = 500; // iterations of power iteration
+    let lanczos_steps = 100; // steps of the Lanczos recursion
+    let broadening = 0.05; // half-width of the peaks
+    let frequencies: Vec<f32> = (0..=40).map(|step| step as f32 * 0.1).collect(); // values of ω
+
+    // Defining the Hamiltonian, the model and the sectors
+    let hamiltonian = Hamiltonian::<AFH>::new(s);
+    let model = Model::new(base, length, total_s_z)?;
+    let ground_state_sector = Sector::new(&model, Parity::Even, Parity::Even, Momentum::Zero)?;
+    let target_sector = Sector::new(&model, Parity::Odd, Parity::Odd, Momentum::Pi)?;
+
+    // Finding the ground state and the continued fraction
+    let ground_state = ground_state_sector.find_eigenstate(&hamiltonian, iterations, &[])?;
+    let continued_fraction = get_dynamical_structure_factor(
+        &hamiltonian,
+        &ground_state,
+        &ground_state_sector,
+        &target_sector,
+        SpinOperator::Z,
+        PI,
+        lanczos_steps,
+    )?;
+    let spectral_function = continued_fraction.get_spectral_function(&frequencies, broadening);
+
+    println!("Total weight: {}", continued_fraction.weight);
+
+    for (frequency, intensity) in spectral_function.frequencies.iter().zip(spectral_function.intensities.iter()) {
+        println!("ω: {:.1}, S(π, ω): {}", frequency, intensity);
+    }
+
+    Ok(())
+}
\ No newline at end of file
@@ -0,0 +1,49 @@
+//! In this example, the specific heat, the entropy and the uniform 
+//! susceptibility of the S=1 AFH chain of length 8 are calculated 
+//! with the finite-temperature Lanczos method. The susceptibility 
+//! vanishes exponentially at low temperatures because of the spectral 
+//! gap, and the entropy per site approaches ln(3) at high temperatures. 
+//! Below the lowest excitation energies, the random states give large 
+//! statistical errors, so the lowest temperature is 0.2.
+use quantum_spin_chains::error::Error;
+use quantum_spin_chains::hamiltonians::{Hamiltonian, AFH};
+use quantum_spin_chains::model::ModelBuilder;
+use quantum_spin_chains::symmetries::Reflection;
+use quantum_spin_chains::thermodynamics::get_thermodynamics;
+
+fn main() -> Result<(), Error> {
+    // Defining parameters of the chain and the calculation
+    let base = 3; // corresponds to S=1
+    let length = 8; // chain length
+    let s = 1.0; // refers to s*H_AFH + (1-s)*H_triv=H_AFH
+    let samples = 20; // random states per sector
+    let lanczos_steps = 60; // steps of the Lanczos recursion
+    let temperatures: Vec<f32> = (2..=30).map(|step| step as f32 * 0.1).collect(); // values of T
+
+    // Defining the Hamiltonian and the symmetries used in every sector
+    let hamiltonian = Hamiltonian::<AFH>::new(s);
+    let model_builder = ModelBuilder::new(base, length)
+        .time_reversal(true)
+        .reflection(Some(Reflection::Bond));
+
+    let thermodynamic_points = get_thermodynamics(
+        &hamiltonian,
+        model_builder,
+        samples,
+        lanczos_steps,
+        &temperatures,
+    )?;
+
+    for thermodynamic_point in thermodynamic_points.iter() {
+        println!(
+            "T: {:.1}, energy: {}, specific heat: {}, entropy: {}, susceptibility: {}",
+            thermodynamic_point.temperature,
+            thermodynamic_point.energy,
+            thermodynamic_point.specific_heat,
+            thermodynamic_point.entropy,
+            thermodynamic_point.susceptibility,
+        );
+    }
+
+    Ok(())
+}
\ No newline at end of file
@@ -0,0 +1,44 @@
+//! In this example, the π-rotation eigenvalues of the ground manifold 
+//! of the open S=1 AKLT chain of length 8 are calculated. The four 
+//! degenerate ground states carry all four irreducible representations 
+//! of D2, which shows that the spin-1/2 edge states transform 
+//! projectively. For comparison, the trivial Hamiltonian (s=0) has a 
+//! unique ground state.
+use quantum_spin_chains::error::Error;
+use quantum_spin_chains::hamiltonians::{Hamiltonian, AKLT};
+use quantum_spin_chains::spt::{count_irreps, get_ground_manifold};
+
+fn main() -> Result<(), Error> {
+    // Defining parameters of the chain and the calculation
+    let base = 3; // corresponds to S=1
+    let length = 8; // chain length
+    let iterations = 2000; // iterations of power iteration
+    let threads = 1; // threads used by the calculation
+    let energy_window = 0.01; // energy window of the ground manifold
+
+    for s in [1.0, 0.0] {
+        let hamiltonian = Hamiltonian::<AKLT>::new(s);
+        let ground_manifold = get_ground_manifold(
+            &hamiltonian,
+            base,
+            length,
+            iterations,
+            threads
+        )?;
+
+        println!("s = {}", s);
+
+        for state in ground_manifold.iter() {
+            println!(
+                "Energy: {}, exp(iπS^x): {}, exp(iπS^z): {}",
+                state.energy,
+                state.x_eigenvalue,
+                state.z_eigenvalue
+            );
+        }
+
+        println!("Irreducible representations: {}", count_irreps(&ground_manifold, energy_window));
+    }
+
+    Ok(())
+}
\ No newline at end of file
@@ -0,0 +1,51 @@
+//! In this example, the S=1 chain is followed along the path 
+//! s*H_AKLT + (1-s)*H_triv from the trivial phase (s=0) to the Haldane 
+//! phase (s=1) for two chain lengths. The fidelity susceptibility of the 
+//! symmetric ground state peaks near the transition between the phases, 
+//! and the peak grows with the chain length.
+use quantum_spin_chains::basis::SzSector;
+use quantum_spin_chains::error::Error;
+use quantum_spin_chains::fidelity::fidelity_sweep;
+use quantum_spin_chains::hamiltonians::{Hamiltonian, AKLT};
+use quantum_spin_chains::model::Model;
+use quantum_spin_chains::sector::Sector;
+use quantum_spin_chains::symmetries::{Momentum, Parity};
+
+fn main() -> Result<(), Error> {
+    // Defining parameters of the chains and the calculation
+    let base = 3; // corresponds to S=1
+    let lengths = [6, 8]; // chain lengths
+    let total_s_z = SzSector::new(0); // total quantum spin in the z-direction is 0
+    let parameters: Vec<f32> = (0..=40).map(|step| step as f32 / 40.0).collect(); // values of s
+    let initial_iterations = 1000; // iterations of power iteration at the first point
+    let iterations = 300; // iterations of power iteration at the other points
+
+    for length in lengths {
+        let model = Model::new(base, length, total_s_z)?;
+        let sectors = [Sector::new(&model, Parity::Even, Parity::Even, Momentum::Zero)?];
+        let fidelity_points = fidelity_sweep(
+            &sectors,
+            &parameters,
+            Hamiltonian::<AKLT>::new,
+            initial_iterations,
+            iterations,
+        )?;
+
+        println!("Length {}", length);
+
+        for fidelity_point in fidelity_points.iter() {
+            // The fidelity is None if the ground state changes sector,
+            // which cannot happen here, since only one sector is used
+            if let (Some(fidelity), Some(fidelity_susceptibility)) = (fidelity_point.fidelity, fidelity_point.fidelity_susceptibility) {
+                println!(
+                    "s: {:.3}, fidelity: {}, fidelity susceptibility per site: {}",
+                    fidelity_point.parameter,
+                    fidelity,
+                    fidelity_susceptibility / length as f32,
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
\ No newline at end of file
@@ -4,42 +4,46 @@
 //! quantum spin in the z-direction is 0. Also, it can 
 //! be shown that the is symmetric (has eigenvalue 1) 
 //! under the three symmetries we consider.
+use quantum_spin_chains::basis::SzSector;
+use quantum_spin_chains::error::Error;
 use quantum_spin_chains::model::Model;
 use quantum_spin_chains::hamiltonians::{Hamiltonian, AKLT};
-use quantum_spin_chains::symmetries::get_symmetry_factors;
+use quantum_spin_chains::sector::Sector;
+use quantum_spin_chains::symmetries::{Momentum, Parity};
 
-fn main() {
+fn main() -> Result<(), Error> {
     // Defining parameters of the chain and the calculation
     let base = 3; // corresponds to S=1
     let length = 10; // chain length
-    let total_s_z = 0; // total quantum spin in the z-direction is 0
-    let time_reversal_eigenvalue = false; // 1
-    let reflection_eigenvalue = false; // 1
-    let translation_eigenvalue = false; // 1
+    let total_s_z = SzSector::new(0); // total quantum spin in the z-direction is 0
+    let time_reversal_eigenvalue = Parity::Even; // 1
+    let reflection_eigenvalue = Parity::Even; // 1
+    let translation_eigenvalue = Momentum::Zero; // 1
     let s = 1.0; // refers to s*H_AKLT + (1-s)*H_triv=H_AKLT
     let iterations = 500; // iterations of power iteration
     
     // Defining the Hamiltonian and the model
     let hamiltonian = Hamiltonian::<AKLT>::new(s);
-    let mut model = Model::new(base, length, total_s_z);
-    let symmetry_factors = get_symmetry_factors(
-        &model.basis_states.symmetry_signs,
+    let model = Model::new(base, length, total_s_z)?;
+    let sector = Sector::new(
+        &model,
         time_reversal_eigenvalue,
         reflection_eigenvalue,
         translation_eigenvalue
-    );
+    )?;
 
     // Defining storage for lower eigenvalues and corresponding
     // eigenvectors (not relevant here)
     let lower_eigenpairs = Vec::new();
     
     // Finding the ground state
-    let (_gs, gs_energy) = model.find_eigenstate(
+    let (_gs, gs_energy) = sector.find_eigenstate(
         &hamiltonian,
         iterations,
-        &symmetry_factors,
         &lower_eigenpairs
-    );
+    )?;
 
     println!("Ground state energy: {}", gs_energy);
+
+    Ok(())
 }
\ No newline at end of file
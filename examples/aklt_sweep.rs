@@ -0,0 +1,62 @@
+//! In this example, the S=1 chain of length 8 is followed along the 
+//! path s*H_AKLT + (1-s)*H_triv from the trivial phase (s=0) to the 
+//! Haldane phase (s=1). Every point is started from the eigenstates of 
+//! the previous point, so few iterations are needed. The gap between 
+//! the symmetric ground state and the lowest antisymmetric state is 
+//! followed together with the twist operator, which changes sign 
+//! between the phases.
+use quantum_spin_chains::basis::SzSector;
+use quantum_spin_chains::error::Error;
+use quantum_spin_chains::model::Model;
+use quantum_spin_chains::hamiltonians::{Hamiltonian, AKLT};
+use quantum_spin_chains::observables::get_twist_expectation_value;
+use quantum_spin_chains::sector::Sector;
+use quantum_spin_chains::states::State;
+use quantum_spin_chains::sweep::sweep;
+use quantum_spin_chains::symmetries::{Momentum, Parity};
+
+fn main() -> Result<(), Error> {
+    // Defining parameters of the chain and the calculation
+    let base = 3; // corresponds to S=1
+    let length = 8; // chain length
+    let total_s_z = SzSector::new(0); // total quantum spin in the z-direction is 0
+    let parameters: Vec<f32> = (0..=20).map(|step| step as f32 / 20.0).collect(); // values of s
+    let levels = 1; // eigenstates per sector
+    let initial_iterations = 1000; // iterations of power iteration at the first point
+    let iterations = 200; // iterations of power iteration at the other points
+
+    // Defining the model and the sectors of the ground state and 
+    // the first excited state
+    let model = Model::new(base, length, total_s_z)?;
+    let sectors = [
+        Sector::new(&model, Parity::Even, Parity::Even, Momentum::Zero)?,
+        Sector::new(&model, Parity::Odd, Parity::Odd, Momentum::Pi)?,
+    ];
+
+    // The real part of the twist operator of the ground state
+    let twist = |state: &State, sector: &Sector| {
+        get_twist_expectation_value(state, sector).map(|twist| twist.re)
+    };
+
+    let sweep_points = sweep(
+        &sectors,
+        &parameters,
+        Hamiltonian::<AKLT>::new,
+        levels,
+        initial_iterations,
+        iterations,
+        &[&twist]
+    )?;
+
+    for sweep_point in sweep_points.iter() {
+        println!(
+            "s: {:.2}, ground state energy: {}, gap: {}, twist: {}",
+            sweep_point.parameter,
+            sweep_point.ground_state_energy,
+            sweep_point.gap.unwrap_or(f32::NAN),
+            sweep_point.observables[0]
+        );
+    }
+
+    Ok(())
+}
\ No newline at end of file
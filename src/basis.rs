@@ -1,103 +1,232 @@
 //! This module contains the [`BasisStates`] struct,
 //! which is a symmetrized version of the natural basis.
-use crate::symmetries::get_eq_class;
+//! Only the representers of the equivalence classes are
+//! stored, so the memory usage is proportional to the
+//! number of equivalence classes rather than to the
+//! dimension of the Hilbert space.
+use crate::configurations::Configurations;
+use crate::error::Error;
+use crate::spin_half;
+use crate::symmetries::{is_representer, get_symmetry_sign_counts, Symmetries};
 
-/// A representer of an equivalence class of basis states.
-#[derive(PartialEq, Debug)]
-pub struct Representer {
-    /// The numeric value of the basis state.
-    pub value: usize,
-    /// The digits of the basis state.
-    pub digits: Vec<u8>,
-    /// The sigmas corresponding to the digits of the basis state.
-    pub sigmas: Vec<f32>,
+/// A sector of fixed total quantum spin in the z-direction.
+/// It is stored as twice the total quantum spin, so
+/// half-integer sectors are represented exactly.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct SzSector {
+    /// Twice the total quantum spin in the z-direction.
+    pub twice_s_z: i32,
 }
 
-impl Representer {
-    /// Creates a [`Representer`].
-    fn new(value: usize, digits: &Vec<u8>, spin: f32) -> Self {
-        let mut digits = digits.clone();
-        digits.push(digits[0]);
-        let sigmas = digits
-            .iter()
-            .map(|digit| *digit as f32 - spin)
-            .collect();
-
-        Self {
-            value,
-            digits,
-            sigmas,
-        }
+impl SzSector {
+    /// Creates a [`SzSector`] with an integer total
+    /// quantum spin in the z-direction.
+    pub fn new(s_z: i32) -> Self {
+        Self {twice_s_z: 2 * s_z}
+    }
+
+    /// Creates a [`SzSector`] from twice the total
+    /// quantum spin in the z-direction.
+    pub fn from_twice(twice_s_z: i32) -> Self {
+        Self {twice_s_z}
+    }
+
+    /// Gets the sector with the opposite total quantum spin 
+    /// in the z-direction, which time reversal maps the 
+    /// sector to.
+    pub fn inverted(&self) -> Self {
+        Self {twice_s_z: -self.twice_s_z}
+    }
+
+    /// Gets the total quantum spin in the z-direction.
+    pub fn get_s_z(&self) -> f32 {
+        self.twice_s_z as f32 / 2.0
+    }
+
+    /// Checks whether the sector exists for a chain, that 
+    /// is whether |S^z| is at most S*length and S^z-S*length 
+    /// is an integer.
+    pub fn is_valid(&self, base: u8, length: u8) -> bool {
+        let twice_max_s_z = (base as i64 - 1) * length as i64;
+        let twice_s_z = self.twice_s_z as i64;
+
+        twice_s_z.abs() <= twice_max_s_z && (twice_max_s_z + twice_s_z) % 2 == 0
+    }
+
+    /// Gets the sum of the digits of the basis states in
+    /// the sector. The sector must be valid.
+    pub fn get_digit_sum(&self, base: u8, length: u8) -> usize {
+        (((base as i64 - 1) * length as i64 + self.twice_s_z as i64) / 2) as usize
     }
 }
 
-/// All basis states arranged into equivalence classes 
+/// All basis states arranged into equivalence classes
 /// (symmetrized basis states).
 #[derive(PartialEq, Debug)]
 pub struct BasisStates {
     /// Number of equivalence classes.
     pub length: usize,
-    /// Representers of the equivalence classes.
-    pub representers: Vec<Representer>,
-    /// [`BasisStates::representers`] arranged by their [`Representer::value`].
-    pub representer_map: Vec<usize>,
-    /// Symmetry signs of the equivalence classes.
-    pub symmetry_signs: Vec<Vec<[bool; 3]>>,
+    /// Numeric values of the representers of the
+    /// equivalence classes in increasing order.
+    pub representers: Vec<usize>,
+    /// Counts of the symmetry signs of the symmetries
+    /// leaving the representers invariant.
+    pub symmetry_signs: Vec<[u8; 8]>,
+    /// Number of elements of the symmetry group.
+    pub group_order: usize,
+    /// The basis states with the given total quantum 
+    /// spin in the z-direction, if no other symmetries 
+    /// are used. Then the index of a basis state is its 
+    /// rank, so the buckets are not needed.
+    pub configurations: Option<Configurations>,
+    /// Number of low bits discarded when arranging the
+    /// representers into buckets.
+    pub bucket_shift: u32,
+    /// Index of the first representer in each bucket, 
+    /// which narrows down the binary search.
+    pub bucket_offsets: Vec<usize>,
 }
 
 impl BasisStates {
-    /// Creates a [`BasisStates`].
+    /// Creates a [`BasisStates`]. If `total_s_z` is `None`,
+    /// all basis states are used. The symmetries that do 
+    /// not leave the sector invariant are not used.
     pub fn new(
         base: u8,
-        spin: f32,
         length: u8,
-        total_s_z: u8,
-        base_powers: &Vec<usize>,
-    ) -> Self {
-        let max_representer_value = (base as usize).pow(length as u32);
+        total_s_z: Option<SzSector>,
+        symmetries: &Symmetries,
+    ) -> Result<Self, Error> {
+        check_chain(base, length, total_s_z)?;
+
+        let symmetries = &symmetries.restrict(total_s_z);
         let mut representers = Vec::new();
-        let mut representer_map = vec![max_representer_value; max_representer_value];
-        let mut symmetry_signs = vec![Vec::new(); max_representer_value];
-        let allowed_digit_sum = (spin * length as f32) as u8 + total_s_z;
-        let mut index = 0;
-
-        for representer_value in 1..max_representer_value-1 {
-            if representer_map[representer_value] == max_representer_value {
-                let digits = get_digits(representer_value, base, length);
-                let digit_sum: u8 = digits.iter().sum();
-                
-                if digit_sum == allowed_digit_sum {
-                    let representer = Representer::new(
-                        representer_value,
-                        &digits,
-                        spin,
-                    );
-                    representers.push(representer);
-
-                    let eq_class = get_eq_class(
-                        digits,
-                        base,
-                        length,
-                        base_powers,
-                    );
-        
-                    for (other_representer_value, symmetry_sign) in eq_class.iter() {
-                        representer_map[*other_representer_value] = index;
-                        symmetry_signs[*other_representer_value].push(*symmetry_sign);
-                    }
+        let mut symmetry_signs = Vec::new();
+        let max_value = (base as usize).pow(length as u32);
+        let values: Box<dyn Iterator<Item = usize>> = match total_s_z {
+            Some(total_s_z) if base == 2 => Box::new(spin_half::get_configurations(
+                length,
+                total_s_z.get_digit_sum(base, length),
+            )),
+            Some(total_s_z) => Box::new(Configurations::new(
+                base,
+                length,
+                total_s_z.get_digit_sum(base, length),
+            ).iter()),
+            None => Box::new(0..max_value),
+        };
 
-                    index += 1;
-                }
+        for representer_value in values {
+            if is_representer(representer_value, base, length, symmetries) {
+                representers.push(representer_value);
+                symmetry_signs.push(get_symmetry_sign_counts(
+                    representer_value,
+                    base,
+                    length,
+                    symmetries,
+                ));
             }
         }
-        
-        Self {
+
+        let (bucket_shift, bucket_offsets) = get_buckets(&representers, max_value);
+        let group_order = symmetries.get_group_order(length);
+        let configurations = match total_s_z {
+            Some(total_s_z) if group_order == 1 => Some(Configurations::new(
+                base,
+                length,
+                total_s_z.get_digit_sum(base, length),
+            )),
+            _ => None,
+        };
+
+        Ok(Self {
             length: representers.len(),
             representers,
-            representer_map,
             symmetry_signs,
+            group_order,
+            configurations,
+            bucket_shift,
+            bucket_offsets,
+        })
+    }
+
+    /// Finds the index of a representer.
+    pub fn get_index(&self, representer_value: usize) -> Option<usize> {
+        if self.group_order == 1 {
+            return match &self.configurations {
+                Some(configurations) => configurations
+                    .contains(representer_value)
+                    .then(|| configurations.rank(representer_value)),
+                None => (representer_value < self.length).then_some(representer_value),
+            };
+        }
+
+        let bucket = representer_value >> self.bucket_shift;
+        let start = *self.bucket_offsets.get(bucket)?;
+        let end = *self.bucket_offsets.get(bucket + 1)?;
+
+        self.representers[start..end]
+            .binary_search(&representer_value)
+            .ok()
+            .map(|index| start + index)
+    }
+}
+
+/// Checks that a chain and a sector of it exist.
+pub fn check_chain(
+    base: u8,
+    length: u8,
+    total_s_z: Option<SzSector>,
+) -> Result<(), Error> {
+    if base == 0 {
+        return Err(Error::InvalidBase(base));
+    }
+
+    if length < 2 {
+        return Err(Error::InvalidLength(length));
+    }
+
+    match (base as usize).checked_pow(length as u32) {
+        Some(dimension) if dimension <= isize::MAX as usize => (),
+        _ => return Err(Error::DimensionOverflow {base, length}),
+    }
+
+    if let Some(total_s_z) = total_s_z {
+        if !total_s_z.is_valid(base, length) {
+            return Err(Error::InvalidSzSector {
+                base,
+                length,
+                twice_s_z: total_s_z.twice_s_z,
+            });
         }
     }
+
+    Ok(())
+}
+
+/// Arranges the representers into buckets according to
+/// their highest bits, with roughly one bucket per
+/// representer.
+fn get_buckets(
+    representers: &[usize],
+    max_value: usize,
+) -> (u32, Vec<usize>) {
+    let value_bits = usize::BITS - max_value.leading_zeros();
+    let bucket_bits = (usize::BITS - representers.len().leading_zeros()).min(value_bits);
+    let bucket_shift = value_bits - bucket_bits;
+    let mut bucket_offsets = Vec::with_capacity((1 << bucket_bits) + 1);
+
+    for (index, representer_value) in representers.iter().enumerate() {
+        while bucket_offsets.len() <= representer_value >> bucket_shift {
+            bucket_offsets.push(index);
+        }
+    }
+
+    while bucket_offsets.len() <= 1 << bucket_bits {
+        bucket_offsets.push(representers.len());
+    }
+
+    (bucket_shift, bucket_offsets)
 }
 
 /// Converts an integer into its digits.
@@ -115,4 +244,61 @@ pub fn get_digits(
     }
 
     digits
+}
+
+/// Converts an integer into its digits, reusing the
+/// allocation of `digits`. The first digit is repeated
+/// at the end, since the chain is periodic.
+pub fn write_digits(
+    value: usize,
+    base: u8,
+    length: u8,
+    digits: &mut Vec<u8>,
+) {
+    let mut value = value;
+    digits.clear();
+
+    if base == 2 {
+        digits.extend((0..length).map(|index| ((value >> index) & 1) as u8));
+        digits.push(digits[0]);
+        return;
+    }
+
+    for _ in 0..length {
+        digits.push((value % base as usize) as u8);
+        value /= base as usize;
+    }
+
+    digits.push(digits[0]);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::symmetries::Reflection;
+
+    #[test]
+    fn index_agrees_with_linear_search() {
+        let all_symmetries = Symmetries::all();
+        let site_symmetries = Symmetries {
+            reflection: Some(Reflection::Site),
+            ..Symmetries::all()
+        };
+        let no_symmetries = Symmetries::none();
+
+        for (base, length) in [(2, 9), (2, 12), (3, 6), (3, 7), (4, 5)] {
+            for total_s_z in [None, Some(SzSector::from_twice(((base as i32 - 1) * length as i32) % 2))] {
+                for symmetries in [&all_symmetries, &site_symmetries, &no_symmetries] {
+                    let basis_states = BasisStates::new(base, length, total_s_z, symmetries).unwrap();
+                    let max_value = (base as usize).pow(length as u32);
+
+                    for value in 0..max_value {
+                        let expected = basis_states.representers.iter().position(|representer| *representer == value);
+
+                        assert_eq!(basis_states.get_index(value), expected, "base {} length {} value {}", base, length, value);
+                    }
+                }
+            }
+        }
+    }
 }
\ No newline at end of file
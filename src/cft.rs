@@ -0,0 +1,280 @@
+//! This module contains the extraction of conformal
+//! field theory (CFT) data from the finite-size spectrum
+//! of a critical periodic chain. The ground state energy
+//! per site is E_0(L)/L = e_∞ - πvc/(6L^2), and the
+//! excitation energies are E(L) - E_0(L) = 2πvx/L, where
+//! v is the velocity, c is the central charge and x is
+//! a scaling dimension. Marginal operators, as in the
+//! S=1/2 AFH chain, give logarithmic corrections, which
+//! the extrapolations in 1/L^2 do not remove.
+use crate::error::Error;
+use crate::hamiltonians::Implemented;
+use crate::model::Model;
+use crate::scaling::{fit_power_law, Extrapolation};
+use crate::sector::Sector;
+use crate::symmetries::{Momentum, Parity};
+use std::f32::consts::PI;
+
+/// The lowest eigenvalue of a sector of a [`Model`].
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct SectorEnergy {
+    pub time_reversal: Parity,
+    pub reflection: Parity,
+    pub translation: Momentum,
+    pub energy: f32,
+}
+
+/// The fit of the ground state energies per site to
+/// e_∞ - πvc/(6L^2).
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct GroundStateFit {
+    /// The energy per site e_∞ of the infinite chain.
+    pub energy_per_site: Extrapolation,
+    /// The product of the velocity and the central
+    /// charge.
+    pub velocity_times_central_charge: Extrapolation,
+}
+
+/// How the velocity is found.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Velocity {
+    /// The velocity is known, for example π/2 for the
+    /// S=1/2 AFH chain.
+    Known(f32),
+    /// The velocity is found from the excitation energies
+    /// with a given index, whose scaling dimension is
+    /// known.
+    Gap {
+        index: usize,
+        scaling_dimension: f32,
+    },
+}
+
+/// The CFT data of a critical chain.
+#[derive(Clone, PartialEq, Debug)]
+pub struct CftData {
+    pub energy_per_site: Extrapolation,
+    pub velocity: Extrapolation,
+    pub central_charge: Extrapolation,
+    /// The scaling dimension of every list of excitation
+    /// energies.
+    pub scaling_dimensions: Vec<Extrapolation>,
+}
+
+/// Finds the lowest eigenvalue of every nonempty sector
+/// of a [`Model`] given by [`Sector::get_all`]. Only the
+/// eigenvalues of the symmetries that are used are
+/// distinguished.
+pub fn get_sector_energies<T: Implemented>(
+    hamiltonian: &T,
+    model: &Model,
+    iterations: u32,
+) -> Result<Vec<SectorEnergy>, Error> {
+    let mut sector_energies = Vec::new();
+
+    for sector in Sector::get_all(model)? {
+        let (_, energy) = sector.find_eigenstate(hamiltonian, iterations, &[])?;
+        sector_energies.push(SectorEnergy {
+            time_reversal: sector.time_reversal,
+            reflection: sector.reflection,
+            translation: sector.translation,
+            energy,
+        });
+    }
+
+    Ok(sector_energies)
+}
+
+/// Fits the ground state energies per site to
+/// e_∞ - πvc/(6L^2). At least three lengths are
+/// required.
+pub fn fit_ground_state_energies(energies_per_site: &[(u8, f32)]) -> Result<GroundStateFit, Error> {
+    let fit = fit_power_law(energies_per_site, 2.0)?;
+    let factor = -6.0 / PI;
+
+    Ok(GroundStateFit {
+        energy_per_site: fit.extrapolation,
+        velocity_times_central_charge: Extrapolation {
+            value: factor * fit.amplitude,
+            uncertainty: factor.abs() * fit.amplitude_uncertainty,
+        },
+    })
+}
+
+/// Finds the velocity from excitation energies with a
+/// known scaling dimension, by extrapolating
+/// L(E(L) - E_0(L))/(2πx) in 1/L^2. At least three
+/// lengths are required.
+pub fn fit_velocity(
+    gaps: &[(u8, f32)],
+    scaling_dimension: f32,
+) -> Result<Extrapolation, Error> {
+    let velocities: Vec<(u8, f32)> = gaps
+        .iter()
+        .map(|(length, gap)| (*length, get_scaled_gap(*length, *gap) / scaling_dimension))
+        .collect();
+
+    Ok(fit_power_law(&velocities, 2.0)?.extrapolation)
+}
+
+/// Finds a scaling dimension from excitation energies,
+/// by extrapolating L(E(L) - E_0(L))/(2πv) in 1/L^2.
+/// The relative uncertainty of the velocity is added.
+/// At least three lengths are required.
+pub fn fit_scaling_dimension(
+    gaps: &[(u8, f32)],
+    velocity: Extrapolation,
+) -> Result<Extrapolation, Error> {
+    let scaled_gaps: Vec<(u8, f32)> = gaps
+        .iter()
+        .map(|(length, gap)| (*length, get_scaled_gap(*length, *gap)))
+        .collect();
+    let scaled_gap = fit_power_law(&scaled_gaps, 2.0)?.extrapolation;
+
+    Ok(divide(scaled_gap, velocity))
+}
+
+/// Gets the central charge from the fit of the ground
+/// state energies and the velocity. The relative
+/// uncertainties are added.
+pub fn get_central_charge(
+    ground_state_fit: &GroundStateFit,
+    velocity: Extrapolation,
+) -> Extrapolation {
+    divide(ground_state_fit.velocity_times_central_charge, velocity)
+}
+
+/// Gets the CFT data from the ground state energies per
+/// site and lists of excitation energies above the
+/// ground state, which all have the same lengths.
+pub fn get_cft_data(
+    energies_per_site: &[(u8, f32)],
+    gaps: &[Vec<(u8, f32)>],
+    velocity: Velocity,
+) -> Result<CftData, Error> {
+    let ground_state_fit = fit_ground_state_energies(energies_per_site)?;
+    let velocity = match velocity {
+        Velocity::Known(velocity) => Extrapolation {
+            value: velocity,
+            uncertainty: 0.0,
+        },
+        Velocity::Gap {index, scaling_dimension} => fit_velocity(
+            gaps.get(index).map(Vec::as_slice).unwrap_or(&[]),
+            scaling_dimension,
+        )?,
+    };
+    let scaling_dimensions = gaps
+        .iter()
+        .map(|gaps| fit_scaling_dimension(gaps, velocity))
+        .collect::<Result<Vec<Extrapolation>, Error>>()?;
+
+    Ok(CftData {
+        energy_per_site: ground_state_fit.energy_per_site,
+        velocity,
+        central_charge: get_central_charge(&ground_state_fit, velocity),
+        scaling_dimensions,
+    })
+}
+
+/// Gets L(E(L) - E_0(L))/(2π), which is vx.
+fn get_scaled_gap(length: u8, gap: f32) -> f32 {
+    length as f32 * gap / (2.0 * PI)
+}
+
+/// Divides two values and adds their relative
+/// uncertainties.
+fn divide(numerator: Extrapolation, denominator: Extrapolation) -> Extrapolation {
+    let value = numerator.value / denominator.value;
+    let relative_uncertainty = (numerator.uncertainty / numerator.value).abs()
+        + (denominator.uncertainty / denominator.value).abs();
+
+    Extrapolation {
+        value,
+        uncertainty: (value * relative_uncertainty).abs(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::basis::SzSector;
+    use crate::hamiltonians::{Hamiltonian, AFH};
+
+    #[test]
+    fn odd_lengths_have_no_momentum_pi() {
+        let hamiltonian = Hamiltonian::<AFH>::new(1.0);
+
+        for length in [5, 6] {
+            let model = Model::new(2, length, SzSector::from_twice(length as i32 % 2)).unwrap();
+            let sector_energies = get_sector_energies(&hamiltonian, &model, 500).unwrap();
+            let has_momentum_pi = sector_energies
+                .iter()
+                .any(|sector_energy| sector_energy.translation == Momentum::Pi);
+
+            assert_eq!(has_momentum_pi, length % 2 == 0);
+        }
+    }
+
+    #[test]
+    fn ground_state_fit_recovers_the_synthetic_data() {
+        let (energy_per_site, velocity, central_charge) = (-0.75, 1.5, 0.5);
+        // E_0(L) = e_∞L - πvc/(6L).
+        let energies_per_site: Vec<(u8, f32)> = [6u8, 8, 10, 12]
+            .iter()
+            .map(|length| {
+                let length_f32 = *length as f32;
+                let energy = energy_per_site * length_f32 - PI * velocity * central_charge / (6.0 * length_f32);
+
+                (*length, energy / length_f32)
+            })
+            .collect();
+        let ground_state_fit = fit_ground_state_energies(&energies_per_site).unwrap();
+
+        assert!((ground_state_fit.energy_per_site.value - energy_per_site).abs() < 1e-5);
+        assert!((ground_state_fit.velocity_times_central_charge.value - velocity * central_charge).abs() < 1e-3);
+    }
+
+    #[test]
+    fn scaling_dimension_fit_recovers_the_synthetic_data() {
+        let (velocity, scaling_dimension) = (1.5, 0.125);
+        // E(L) - E_0(L) = 2πvx/L plus a correction, which
+        // the extrapolation removes.
+        let gaps: Vec<(u8, f32)> = [6u8, 8, 10, 12]
+            .iter()
+            .map(|length| {
+                let length_f32 = *length as f32;
+
+                (*length, 2.0 * PI * velocity * scaling_dimension / length_f32 + 0.3 / length_f32.powi(3))
+            })
+            .collect();
+        let known_velocity = Extrapolation {
+            value: velocity,
+            uncertainty: 0.0,
+        };
+
+        assert!((fit_scaling_dimension(&gaps, known_velocity).unwrap().value - scaling_dimension).abs() < 1e-4);
+        assert!((fit_velocity(&gaps, scaling_dimension).unwrap().value - velocity).abs() < 1e-3);
+    }
+
+    #[test]
+    fn heisenberg_chain_has_central_charge_one() {
+        let hamiltonian = Hamiltonian::<AFH>::new(1.0);
+        let energies_per_site: Vec<(u8, f32)> = [8, 10, 12, 14]
+            .iter()
+            .map(|length| {
+                let model = Model::new(2, *length, SzSector::new(0)).unwrap();
+                let energy = get_sector_energies(&hamiltonian, &model, 1000)
+                    .unwrap()
+                    .iter()
+                    .map(|sector_energy| sector_energy.energy)
+                    .fold(f32::INFINITY, f32::min);
+
+                (*length, energy / *length as f32)
+            })
+            .collect();
+        let cft_data = get_cft_data(&energies_per_site, &[], Velocity::Known(PI / 2.0)).unwrap();
+
+        assert!((cft_data.central_charge.value - 1.0).abs() < 0.1);
+        assert!((cft_data.energy_per_site.value - (0.25 - 2.0f32.ln())).abs() < 1e-2);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,262 @@
+//! This module contains the [`Configurations`] struct,
+//! which enumerates, ranks and unranks the basis states
+//! with a given sum of digits, that is a given total
+//! quantum spin in the z-direction.
+/// All basis states with a given sum of digits in
+/// increasing order.
+#[derive(PartialEq, Debug)]
+pub struct Configurations {
+    pub base: u8,
+    pub length: u8,
+    pub digit_sum: usize,
+    /// `counts[sites][digit_sum]` is the number of basis
+    /// states on `sites` sites with sum of digits `digit_sum`.
+    pub counts: Vec<Vec<usize>>,
+}
+
+impl Configurations {
+    /// Creates a [`Configurations`].
+    pub fn new(
+        base: u8,
+        length: u8,
+        digit_sum: usize,
+    ) -> Self {
+        let counts = get_counts(base, length, digit_sum);
+
+        Self {
+            base,
+            length,
+            digit_sum,
+            counts,
+        }
+    }
+
+    /// Gets the number of basis states.
+    pub fn count(&self) -> usize {
+        self.counts[self.length as usize][self.digit_sum]
+    }
+
+    /// Checks whether a basis state has the given sum of 
+    /// digits.
+    pub fn contains(&self, value: usize) -> bool {
+        let mut remaining_value = value;
+        let mut digit_sum = 0;
+
+        for _ in 0..self.length {
+            digit_sum += remaining_value % self.base as usize;
+            remaining_value /= self.base as usize;
+        }
+
+        remaining_value == 0 && digit_sum == self.digit_sum
+    }
+
+    /// Gets the position of a basis state in increasing order.
+    /// The basis state must have the given sum of digits.
+    pub fn rank(&self, value: usize) -> usize {
+        let mut rank = 0;
+        let mut remaining_digit_sum = self.digit_sum;
+
+        for sites in (0..self.length as usize).rev() {
+            let base_power = (self.base as usize).pow(sites as u32);
+            let digit = (value / base_power) % self.base as usize;
+
+            for smaller_digit in 0..digit.min(remaining_digit_sum + 1) {
+                rank += self.counts[sites][remaining_digit_sum - smaller_digit];
+            }
+
+            remaining_digit_sum -= digit;
+        }
+
+        rank
+    }
+
+    /// Gets the basis state at a position in increasing order.
+    pub fn unrank(&self, rank: usize) -> usize {
+        let mut rank = rank;
+        let mut value = 0;
+        let mut remaining_digit_sum = self.digit_sum;
+
+        for sites in (0..self.length as usize).rev() {
+            let base_power = (self.base as usize).pow(sites as u32);
+
+            for digit in 0..self.base as usize {
+                let count = if digit <= remaining_digit_sum {
+                    self.counts[sites][remaining_digit_sum - digit]
+                } else {
+                    0
+                };
+
+                if rank < count {
+                    value += digit * base_power;
+                    remaining_digit_sum -= digit;
+                    break;
+                }
+
+                rank -= count;
+            }
+        }
+
+        value
+    }
+
+    /// Iterates over the basis states in increasing order,
+    /// starting at a position.
+    pub fn iter_from(&self, rank: usize) -> ConfigurationIter {
+        if rank >= self.count() {
+            return ConfigurationIter {
+                base: self.base,
+                digits: Vec::new(),
+                value: None,
+            };
+        }
+
+        let value = self.unrank(rank);
+        let digits = (0..self.length)
+            .map(|sites| ((value / (self.base as usize).pow(sites as u32)) % self.base as usize) as u8)
+            .collect();
+
+        ConfigurationIter {
+            base: self.base,
+            digits,
+            value: Some(value),
+        }
+    }
+
+    /// Iterates over the basis states in increasing order.
+    pub fn iter(&self) -> ConfigurationIter {
+        self.iter_from(0)
+    }
+}
+
+/// Iterator over the basis states of [`Configurations`].
+pub struct ConfigurationIter {
+    base: u8,
+    digits: Vec<u8>,
+    value: Option<usize>,
+}
+
+impl Iterator for ConfigurationIter {
+    type Item = usize;
+
+    /// Gets the next basis state by increasing the lowest
+    /// digit that can be increased while a lower digit can
+    /// be decreased, and then arranging the lower digits
+    /// to give the smallest value.
+    fn next(&mut self) -> Option<usize> {
+        let value = self.value?;
+        let mut lower_digit_sum = 0;
+        let mut next_value = None;
+
+        for index in 0..self.digits.len() {
+            if lower_digit_sum > 0 && self.digits[index] < self.base - 1 {
+                self.digits[index] += 1;
+                let mut remaining_digit_sum = lower_digit_sum - 1;
+
+                for lower_digit in self.digits[..index].iter_mut() {
+                    *lower_digit = remaining_digit_sum.min(self.base as usize - 1) as u8;
+                    remaining_digit_sum -= *lower_digit as usize;
+                }
+
+                next_value = Some(
+                    self.digits
+                        .iter()
+                        .rev()
+                        .fold(0, |value, digit| value * self.base as usize + *digit as usize)
+                );
+                break;
+            }
+
+            lower_digit_sum += self.digits[index] as usize;
+        }
+
+        self.value = next_value;
+
+        Some(value)
+    }
+}
+
+/// Counts the basis states with each sum of digits
+/// up to `digit_sum` on each number of sites.
+fn get_counts(
+    base: u8,
+    length: u8,
+    digit_sum: usize,
+) -> Vec<Vec<usize>> {
+    let mut counts = vec![vec![0; digit_sum + 1]; length as usize + 1];
+    counts[0][0] = 1;
+
+    for sites in 1..=length as usize {
+        for sum in 0..=digit_sum {
+            counts[sites][sum] = (0..base as usize)
+                .take_while(|digit| *digit <= sum)
+                .map(|digit| counts[sites - 1][sum - digit])
+                .sum();
+        }
+    }
+
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Gets the basis states with a given sum of digits by
+    /// filtering all basis states.
+    fn get_filtered_values(base: u8, length: u8, digit_sum: usize) -> Vec<usize> {
+        (0..(base as usize).pow(length as u32))
+            .filter(|value| {
+                let mut remaining_value = *value;
+                let mut sum = 0;
+
+                for _ in 0..length {
+                    sum += remaining_value % base as usize;
+                    remaining_value /= base as usize;
+                }
+
+                sum == digit_sum
+            })
+            .collect()
+    }
+
+    #[test]
+    fn rank_and_unrank_agree_with_enumeration() {
+        for (base, length) in [(2, 8), (3, 6), (4, 5), (5, 4)] {
+            for digit_sum in 0..=(base as usize - 1) * length as usize {
+                let configurations = Configurations::new(base, length, digit_sum);
+                let values = get_filtered_values(base, length, digit_sum);
+
+                assert_eq!(configurations.count(), values.len());
+
+                for (rank, value) in values.iter().enumerate() {
+                    assert_eq!(configurations.unrank(rank), *value);
+                    assert_eq!(configurations.rank(*value), rank);
+                    assert!(configurations.contains(*value));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn iteration_agrees_with_enumeration() {
+        for (base, length) in [(2, 8), (3, 6), (4, 5)] {
+            for digit_sum in 0..=(base as usize - 1) * length as usize {
+                let configurations = Configurations::new(base, length, digit_sum);
+                let values = get_filtered_values(base, length, digit_sum);
+
+                assert_eq!(configurations.iter().collect::<Vec<usize>>(), values);
+                assert_eq!(configurations.iter_from(values.len() / 2).collect::<Vec<usize>>(), values[values.len() / 2..]);
+                assert_eq!(configurations.iter_from(values.len()).count(), 0);
+            }
+        }
+    }
+
+    #[test]
+    fn contains_rejects_other_values() {
+        let configurations = Configurations::new(3, 4, 4);
+
+        assert!(!configurations.contains(0));
+        assert!(!configurations.contains(3usize.pow(4) + 4));
+        assert!(configurations.contains(2 + 2 * 3));
+    }
+}
\ No newline at end of file
@@ -0,0 +1,325 @@
+//! This module contains diagnostics that check whether
+//! a Hamiltonian has the symmetries selected for a
+//! [`Model`], by building it in the full basis of a
+//! small chain and in the symmetrized basis of every
+//! sector. The matrices are stored by columns, so the
+//! diagnostics are only meant for small chains.
+use crate::basis::write_digits;
+use crate::error::Error;
+use crate::hamiltonians::MatrixElements;
+use crate::model::Model;
+use crate::sector::Sector;
+use crate::symmetries::{get_digit_sum, reflect, time_reverse, translate, Momentum, Parity};
+
+/// A property that a Hamiltonian must have for the
+/// symmetrized basis to be used.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Property {
+    /// Conservation of the total quantum spin in the
+    /// z-direction.
+    SzConservation,
+    /// Commutation with time reversal (spin flip).
+    TimeReversal,
+    /// Commutation with the selected reflection.
+    Reflection,
+    /// Commutation with translation by one site.
+    Translation,
+    /// Symmetry of the matrix in the full basis.
+    Hermiticity,
+    /// Hermiticity of the matrix in the symmetrized
+    /// basis of a sector.
+    SectorHermiticity {
+        time_reversal: Parity,
+        reflection: Parity,
+        translation: Momentum,
+    },
+}
+
+/// A property that a Hamiltonian does not have.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Violation {
+    pub property: Property,
+    /// The largest deviation of a matrix element.
+    pub deviation: f32,
+}
+
+/// Checks that a Hamiltonian is real symmetric and
+/// commutes with the symmetries of a [`Model`], and
+/// that it is Hermitian in the symmetrized basis of
+/// every sector given by [`Sector::get_all`]. Deviations
+/// up to `tolerance` are accepted. The properties that
+/// are violated are returned.
+pub fn check_hamiltonian<T: MatrixElements>(
+    hamiltonian: &T,
+    model: &Model,
+    tolerance: f32,
+) -> Result<Vec<Violation>, Error> {
+    let columns = get_full_columns(hamiltonian, model);
+    let mut deviations = Vec::new();
+
+    if model.symmetries.s_z_conservation {
+        let deviation = columns
+            .iter()
+            .enumerate()
+            .flat_map(|(value, column)| column.iter().map(move |element| (value, element)))
+            .filter(|(value, (new_value, _))| {
+                get_digit_sum(*value, model.base, model.length)
+                    != get_digit_sum(*new_value, model.base, model.length)
+            })
+            .fold(0.0, |deviation: f32, (_, (_, matrix_element))| deviation.max(matrix_element.abs()));
+        deviations.push((Property::SzConservation, deviation));
+    }
+
+    if model.symmetries.time_reversal {
+        let deviation = get_commutator_deviation(&columns, |value| {
+            Ok(time_reverse(value, model.base, model.length))
+        })?;
+        deviations.push((Property::TimeReversal, deviation));
+    }
+
+    if let Some(reflection) = model.symmetries.reflection {
+        let deviation = get_commutator_deviation(&columns, |value| {
+            reflect(value, model.base, model.length, reflection)
+        })?;
+        deviations.push((Property::Reflection, deviation));
+    }
+
+    if model.symmetries.translation {
+        let deviation = get_commutator_deviation(&columns, |value| {
+            translate(value, model.base, model.length)
+        })?;
+        deviations.push((Property::Translation, deviation));
+    }
+
+    deviations.push((Property::Hermiticity, get_asymmetry(&columns, &[])));
+
+    for sector in Sector::get_all(model)? {
+        let columns = get_sector_columns(hamiltonian, &sector);
+        let deviation = get_asymmetry(&columns, &sector.symmetry_factors.factors);
+        let property = Property::SectorHermiticity {
+            time_reversal: sector.time_reversal,
+            reflection: sector.reflection,
+            translation: sector.translation,
+        };
+        deviations.push((property, deviation));
+    }
+
+    Ok(deviations
+        .into_iter()
+        .filter(|(_, deviation)| *deviation > tolerance)
+        .map(|(property, deviation)| Violation {property, deviation})
+        .collect())
+}
+
+/// Builds the columns of a Hamiltonian in the full
+/// basis, with the rows of every column in increasing
+/// order and without repetitions.
+fn get_full_columns<T: MatrixElements>(
+    hamiltonian: &T,
+    model: &Model,
+) -> Vec<Vec<(usize, f32)>> {
+    let max_value = (model.base as usize).pow(model.length as u32);
+    let mut digits = Vec::with_capacity(model.length as usize + 1);
+    let mut columns = Vec::with_capacity(max_value);
+
+    for value in 0..max_value {
+        let mut column = Vec::new();
+        write_digits(value, model.base, model.length, &mut digits);
+        hamiltonian.get_matrix_elements(value, &digits, model, &mut column);
+        columns.push(merge_elements(column));
+    }
+
+    columns
+}
+
+/// Builds the columns of a Hamiltonian in the
+/// symmetrized basis of a [`Sector`], in the same way
+/// as it is applied by scattering.
+fn get_sector_columns<T: MatrixElements>(
+    hamiltonian: &T,
+    sector: &Sector,
+) -> Vec<Vec<(usize, f32)>> {
+    let model = sector.model;
+    let symmetry_factors = &sector.symmetry_factors;
+    let mut matrix_elements = Vec::new();
+    let mut digits = Vec::with_capacity(model.length as usize + 1);
+    let mut columns = Vec::with_capacity(model.basis_states.length);
+
+    for (basis_state_index, representer_value) in model.basis_states.representers.iter().enumerate() {
+        let symmetry_factor = symmetry_factors.factors[basis_state_index];
+        let mut column = Vec::new();
+
+        if symmetry_factor != 0.0 {
+            write_digits(*representer_value, model.base, model.length, &mut digits);
+            matrix_elements.clear();
+            hamiltonian.get_matrix_elements(*representer_value, &digits, model, &mut matrix_elements);
+
+            for (new_value, matrix_element) in matrix_elements.iter() {
+                let Some((new_basis_state_index, symmetry_sign)) = model.find_representer(*new_value) else {
+                    continue;
+                };
+                let new_symmetry_factor = symmetry_factors.factors[new_basis_state_index];
+
+                if new_symmetry_factor != 0.0 {
+                    let symmetry_ratio = symmetry_factors.get_sign(symmetry_sign) * new_symmetry_factor / symmetry_factor;
+                    column.push((new_basis_state_index, matrix_element * symmetry_ratio));
+                }
+            }
+        }
+
+        columns.push(merge_elements(column));
+    }
+
+    columns
+}
+
+/// Sorts matrix elements by row and adds the ones in
+/// the same row.
+fn merge_elements(mut elements: Vec<(usize, f32)>) -> Vec<(usize, f32)> {
+    elements.sort_by_key(|(row, _)| *row);
+    let mut merged_elements: Vec<(usize, f32)> = Vec::with_capacity(elements.len());
+
+    for (row, matrix_element) in elements {
+        match merged_elements.last_mut() {
+            Some((last_row, last_matrix_element)) if *last_row == row => *last_matrix_element += matrix_element,
+            _ => merged_elements.push((row, matrix_element)),
+        }
+    }
+
+    merged_elements
+}
+
+/// Gets a matrix element from its columns.
+fn get_element(columns: &[Vec<(usize, f32)>], row: usize, column: usize) -> f32 {
+    let column = &columns[column];
+
+    column
+        .binary_search_by_key(&row, |(row, _)| *row)
+        .map(|index| column[index].1)
+        .unwrap_or(0.0)
+}
+
+/// Gets the largest deviation between the matrix
+/// elements of an operator H and those of P H P^-1,
+/// where P permutes the basis states.
+fn get_commutator_deviation<F>(
+    columns: &[Vec<(usize, f32)>],
+    permutation: F,
+) -> Result<f32, Error> where F: Fn(usize) -> Result<usize, Error> {
+    let mut deviation: f32 = 0.0;
+
+    for (column, elements) in columns.iter().enumerate() {
+        let permuted_column = permutation(column)?;
+        let permuted_elements = merge_elements(
+            elements
+                .iter()
+                .map(|(row, matrix_element)| Ok((permutation(*row)?, *matrix_element)))
+                .collect::<Result<Vec<(usize, f32)>, Error>>()?
+        );
+        let other_elements = &columns[permuted_column];
+
+        for (row, matrix_element) in permuted_elements.iter() {
+            deviation = deviation.max((matrix_element - get_element(columns, *row, permuted_column)).abs());
+        }
+
+        for (row, matrix_element) in other_elements.iter() {
+            if permuted_elements.binary_search_by_key(row, |(row, _)| *row).is_err() {
+                deviation = deviation.max(matrix_element.abs());
+            }
+        }
+    }
+
+    Ok(deviation)
+}
+
+/// Gets the largest deviation from M_rs/F_r = M_sr/F_s,
+/// which is Hermiticity with respect to the inner product
+/// of the symmetrized basis with symmetry factors F. If
+/// no symmetry factors are given, it is the symmetry of M.
+fn get_asymmetry(columns: &[Vec<(usize, f32)>], factors: &[f32]) -> f32 {
+    let get_factor = |index: usize| factors.get(index).copied().unwrap_or(1.0);
+    let mut deviation: f32 = 0.0;
+
+    for (column, elements) in columns.iter().enumerate() {
+        for (row, matrix_element) in elements.iter() {
+            let transposed_element = get_element(columns, column, *row);
+            deviation = deviation.max(
+                (matrix_element / get_factor(*row) - transposed_element / get_factor(column)).abs()
+            );
+        }
+    }
+
+    deviation
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::basis::SzSector;
+    use crate::hamiltonians::{Hamiltonian, AFH};
+    use crate::model::ModelBuilder;
+    use crate::symmetries::Reflection;
+    use crate::testing::Perturbed;
+
+    /// Gets the properties that are violated.
+    fn get_violated_properties<T: MatrixElements>(hamiltonian: &T, model: &Model) -> Vec<Property> {
+        check_hamiltonian(hamiltonian, model, 1e-5)
+            .unwrap()
+            .iter()
+            .map(|violation| violation.property)
+            .collect()
+    }
+
+    #[test]
+    fn symmetric_hamiltonians_have_no_violations() {
+        for (base, length, twice_s_z) in [(3, 5, 2), (3, 6, 0), (2, 7, 1), (2, 8, 0)] {
+            let model = Model::new(base, length, SzSector::from_twice(twice_s_z)).unwrap();
+            let violations = check_hamiltonian(&Hamiltonian::<AFH>::new(1.0), &model, 1e-5).unwrap();
+
+            assert!(violations.is_empty(), "base {} length {}: {:?}", base, length, violations);
+        }
+    }
+
+    #[test]
+    fn broken_symmetries_are_reported() {
+        let model = Model::new(3, 6, SzSector::new(0)).unwrap();
+        let site_model = ModelBuilder::new(3, 6)
+            .total_s_z(Some(SzSector::new(0)))
+            .time_reversal(true)
+            .reflection(Some(Reflection::Site))
+            .translation(true)
+            .build()
+            .unwrap();
+
+        // A uniform field breaks time reversal.
+        let mut hamiltonian = Perturbed::new(Hamiltonian::<AFH>::new(1.0));
+        hamiltonian.fields = vec![0.3; 6];
+        assert_eq!(get_violated_properties(&hamiltonian, &model), vec![Property::TimeReversal]);
+
+        // A transverse field breaks the conservation of S^z.
+        let mut hamiltonian = Perturbed::new(Hamiltonian::<AFH>::new(1.0));
+        hamiltonian.transverse_fields = vec![0.3; 6];
+        assert_eq!(get_violated_properties(&hamiltonian, &model), vec![Property::SzConservation]);
+
+        // A dimerization breaks translation by one site,
+        // which makes sectors of nonzero momentum
+        // non-Hermitian.
+        let mut hamiltonian = Perturbed::new(Hamiltonian::<AFH>::new(1.0));
+        hamiltonian.exchanges = vec![0.3, 0.0, 0.3, 0.0, 0.3, 0.0];
+        let properties = get_violated_properties(&hamiltonian, &model);
+        assert_eq!(properties[0], Property::Translation);
+        assert!(properties.len() > 1);
+        assert!(properties[1..].iter().all(|property| matches!(property, Property::SectorHermiticity {..})));
+
+        // A D-term on site 0 breaks translation and the
+        // bond-centered reflection, but not the reflection
+        // centered on site 0.
+        let mut hamiltonian = Perturbed::new(Hamiltonian::<AFH>::new(1.0));
+        hamiltonian.anisotropies = vec![0.5];
+        assert_eq!(
+            get_violated_properties(&hamiltonian, &model),
+            vec![Property::Reflection, Property::Translation],
+        );
+        assert_eq!(get_violated_properties(&hamiltonian, &site_model), vec![Property::Translation]);
+    }
+}
\ No newline at end of file
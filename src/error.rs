@@ -0,0 +1,215 @@
+//! This module contains the [`Error`] enum, which
+//! describes what can go wrong when using the crate.
+use std::fmt;
+
+/// The errors of the crate.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Error {
+    /// The base must be at least 1.
+    InvalidBase(u8),
+    /// The periodic chain must have at least two sites.
+    InvalidLength(u8),
+    /// The total quantum spin in the z-direction is not
+    /// possible for the chain.
+    InvalidSzSector {
+        base: u8,
+        length: u8,
+        twice_s_z: i32,
+    },
+    /// The dimension of the Hilbert space, base^length,
+    /// does not fit in an `isize`.
+    DimensionOverflow {
+        base: u8,
+        length: u8,
+    },
+    /// A [`crate::states::State`] does not have one 
+    /// coefficient per representer of the basis states.
+    DimensionMismatch {
+        expected: usize,
+        found: usize,
+    },
+    /// A vector in the full basis does not have one
+    /// coefficient per basis state of the chain.
+    FullStateLengthMismatch {
+        expected: usize,
+        found: usize,
+    },
+    /// A product state does not have one site state per
+    /// site of the chain.
+    ProductStateLengthMismatch {
+        expected: usize,
+        found: usize,
+    },
+    /// A site state of a product state does not have one
+    /// coefficient per digit.
+    SiteStateLengthMismatch {
+        expected: usize,
+        found: usize,
+    },
+    /// All the symmetry factors vanish, so the sector 
+    /// contains no states.
+    EmptySector,
+    /// The [`crate::states::State`] has no coefficients.
+    EmptyState,
+    /// The norm of a [`crate::states::State`] vanishes
+    /// or is not finite, so it cannot be normalized.
+    VanishingNorm,
+    /// Translation and site-centered reflection are not
+    /// symmetries of a chain with open boundaries.
+    OpenBoundarySymmetry,
+    /// The momentum π is only a translation eigenvalue of
+    /// a chain with an even length.
+    OddLengthMomentum(u8),
+    /// A [`crate::states::State`] belongs to another
+    /// [`crate::sector::Sector`].
+    SectorMismatch {
+        expected: u64,
+        found: u64,
+    },
+    /// Two [`crate::sector::Sector`]s are not related by
+    /// the requested symmetry.
+    IncompatibleSectors,
+    /// An operator has matrix elements that are not real
+    /// for the chain.
+    NonRealOperator,
+    /// The momentum of an operator is neither 0 nor π, so
+    /// it does not map to a sector of a chain using
+    /// translation, whose momenta are real.
+    NonRealMomentum(f32),
+    /// A block of sites is empty or does not fit in the
+    /// chain.
+    InvalidBlock {
+        start: u8,
+        block_length: u8,
+        length: u8,
+    },
+    /// Too few chain lengths were given for an
+    /// extrapolation.
+    TooFewLengths {
+        required: usize,
+        found: usize,
+    },
+    /// The equations of a fit have no unique solution,
+    /// for example because a chain length is repeated.
+    SingularFit,
+    /// The QL algorithm did not converge for an
+    /// eigenvalue of a tridiagonal matrix.
+    NoConvergence,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::InvalidBase(base) => write!(
+                formatter,
+                "the base must be at least 1, but it is {}",
+                base,
+            ),
+            Error::InvalidLength(length) => write!(
+                formatter,
+                "the chain must have at least two sites, but it has {}",
+                length,
+            ),
+            Error::InvalidSzSector {base, length, twice_s_z} => write!(
+                formatter,
+                "the total quantum spin in the z-direction {} is not possible for S={} and length {}",
+                *twice_s_z as f32 / 2.0,
+                (*base as f32 - 1.0) / 2.0,
+                length,
+            ),
+            Error::DimensionOverflow {base, length} => write!(
+                formatter,
+                "the dimension of the Hilbert space {}^{} is too large",
+                base,
+                length,
+            ),
+            Error::DimensionMismatch {expected, found} => write!(
+                formatter,
+                "the state has {} coefficients, but the basis has {} representers",
+                found,
+                expected,
+            ),
+            Error::FullStateLengthMismatch {expected, found} => write!(
+                formatter,
+                "the full vector has {} coefficients, but the chain has {} basis states",
+                found,
+                expected,
+            ),
+            Error::ProductStateLengthMismatch {expected, found} => write!(
+                formatter,
+                "the product state has {} site states, but the chain has {} sites",
+                found,
+                expected,
+            ),
+            Error::SiteStateLengthMismatch {expected, found} => write!(
+                formatter,
+                "the site state has {} coefficients, but a site has {} basis states",
+                found,
+                expected,
+            ),
+            Error::EmptySector => write!(
+                formatter,
+                "all the symmetry factors vanish, so the sector is empty",
+            ),
+            Error::EmptyState => write!(
+                formatter,
+                "the state has no coefficients",
+            ),
+            Error::VanishingNorm => write!(
+                formatter,
+                "the norm of the state vanishes or is not finite",
+            ),
+            Error::OpenBoundarySymmetry => write!(
+                formatter,
+                "translation and site-centered reflection require periodic boundaries",
+            ),
+            Error::OddLengthMomentum(length) => write!(
+                formatter,
+                "the momentum π requires an even length, but the length is {}",
+                length,
+            ),
+            Error::SectorMismatch {expected, found} => write!(
+                formatter,
+                "the state belongs to sector {}, but sector {} was expected",
+                found,
+                expected,
+            ),
+            Error::IncompatibleSectors => write!(
+                formatter,
+                "the sectors are not related by the symmetry",
+            ),
+            Error::NonRealOperator => write!(
+                formatter,
+                "the operator is not real for this chain",
+            ),
+            Error::NonRealMomentum(momentum) => write!(
+                formatter,
+                "the momentum {} is neither 0 nor π, which translation requires",
+                momentum,
+            ),
+            Error::InvalidBlock {start, block_length, length} => write!(
+                formatter,
+                "the block of {} sites starting at site {} does not fit in a chain of length {}",
+                block_length,
+                start,
+                length,
+            ),
+            Error::TooFewLengths {required, found} => write!(
+                formatter,
+                "the extrapolation requires {} chain lengths, but {} were given",
+                required,
+                found,
+            ),
+            Error::SingularFit => write!(
+                formatter,
+                "the fit has no unique solution",
+            ),
+            Error::NoConvergence => write!(
+                formatter,
+                "the diagonalization of the tridiagonal matrix did not converge",
+            ),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
\ No newline at end of file
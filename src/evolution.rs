@@ -0,0 +1,345 @@
+//! This module contains the real-time evolution of
+//! states, exp(-iHt)|ψ⟩, which is used for quenches:
+//! a product state or a ground state of one Hamiltonian
+//! is evolved with another. The Hamiltonians are real,
+//! so a complex state is stored as two [`State`]s, and
+//! every time step is calculated in the Krylov space of
+//! the Lanczos algorithm.
+use crate::error::Error;
+use crate::hamiltonians::Implemented;
+use crate::lanczos::Tridiagonal;
+use crate::sector::Sector;
+use crate::states::State;
+use num_complex::Complex32;
+
+/// A complex vector in the symmetrized basis of a
+/// [`Sector`], given by its real and imaginary parts.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ComplexState {
+    pub real: State,
+    pub imaginary: State,
+}
+
+/// An observable calculated from a [`ComplexState`] and
+/// the [`Sector`] it belongs to.
+pub type ComplexObservable<'a> = &'a dyn Fn(&ComplexState, &Sector) -> Result<f32, Error>;
+
+/// The results of a [`time_evolve`] at one time.
+#[derive(Clone, PartialEq, Debug)]
+pub struct EvolutionPoint {
+    pub time: f32,
+    /// The change of the norm since the start, which
+    /// vanishes for an exact evolution.
+    pub norm_deviation: f32,
+    /// The change of the energy since the start, which
+    /// vanishes for an exact evolution.
+    pub energy_deviation: f32,
+    pub observables: Vec<f32>,
+}
+
+impl ComplexState {
+    /// Creates a [`ComplexState`] of a [`Sector`] from its
+    /// real part.
+    pub fn from_real(real: State, sector: &Sector) -> Result<Self, Error> {
+        real.check_sector(sector)?;
+
+        Ok(Self {
+            real,
+            imaginary: sector.from_zeros(),
+        })
+    }
+
+    /// Checks that both parts can belong to a [`Sector`].
+    pub fn check_sector(&self, sector: &Sector) -> Result<(), Error> {
+        self.real.check_sector(sector)?;
+        self.imaginary.check_sector(sector)
+    }
+
+    /// Inner product ⟨self|state⟩, which is antilinear
+    /// in this [`ComplexState`].
+    pub fn dot(
+        &self,
+        state: &ComplexState,
+        sector: &Sector,
+    ) -> Result<Complex32, Error> {
+        Ok(Complex32::new(
+            self.real.dot(&state.real, sector)? + self.imaginary.dot(&state.imaginary, sector)?,
+            self.real.dot(&state.imaginary, sector)? - self.imaginary.dot(&state.real, sector)?,
+        ))
+    }
+
+    /// Gets the norm of the [`ComplexState`].
+    pub fn get_norm(&self, sector: &Sector) -> Result<f32, Error> {
+        Ok(self.dot(self, sector)?.re.sqrt())
+    }
+
+    /// Subtracts a complex multiple of another
+    /// [`ComplexState`] from this [`ComplexState`].
+    pub fn subtract(
+        &mut self,
+        state: &ComplexState,
+        scaler: Complex32,
+        threads: usize,
+    ) -> Result<(), Error> {
+        self.real.subtract(&state.real, scaler.re, threads)?;
+        self.real.subtract(&state.imaginary, -scaler.im, threads)?;
+        self.imaginary.subtract(&state.imaginary, scaler.re, threads)?;
+        self.imaginary.subtract(&state.real, scaler.im, threads)
+    }
+
+    /// Gets the probabilities |ψ(v)|^2/⟨ψ|ψ⟩ of the basis
+    /// states v of the full basis, which give the
+    /// expectation values of diagonal operators.
+    pub fn get_probabilities(&self, sector: &Sector) -> Result<Vec<f32>, Error> {
+        let norm_squared = self.dot(self, sector)?.re;
+
+        if norm_squared == 0.0 || !norm_squared.is_finite() {
+            return Err(Error::VanishingNorm);
+        }
+
+        let full_real = self.real.get_full_state(sector)?;
+        let full_imaginary = self.imaginary.get_full_state(sector)?;
+
+        Ok(full_real
+            .iter()
+            .zip(full_imaginary.iter())
+            .map(|(real, imaginary)| (real * real + imaginary * imaginary) / norm_squared)
+            .collect())
+    }
+}
+
+/// Applies a Hamiltonian to a [`ComplexState`], which
+/// acts on the real and imaginary parts separately.
+pub fn apply<T: Implemented>(
+    hamiltonian: &T,
+    input_state: &ComplexState,
+    sector: &Sector,
+) -> Result<ComplexState, Error> {
+    let mut output_state = ComplexState {
+        real: sector.from_zeros(),
+        imaginary: sector.from_zeros(),
+    };
+    hamiltonian.apply(&input_state.real, &mut output_state.real, sector)?;
+    hamiltonian.apply(&input_state.imaginary, &mut output_state.imaginary, sector)?;
+
+    Ok(output_state)
+}
+
+/// Gets the energy ⟨ψ|H|ψ⟩/⟨ψ|ψ⟩ of a [`ComplexState`].
+pub fn get_energy<T: Implemented>(
+    hamiltonian: &T,
+    state: &ComplexState,
+    sector: &Sector,
+) -> Result<f32, Error> {
+    let norm_squared = state.dot(state, sector)?.re;
+
+    if norm_squared == 0.0 || !norm_squared.is_finite() {
+        return Err(Error::VanishingNorm);
+    }
+
+    Ok(state.dot(&apply(hamiltonian, state, sector)?, sector)?.re / norm_squared)
+}
+
+/// Evolves a [`ComplexState`] of a [`Sector`] by
+/// exp(-iHt) in the Krylov space of dimension at most
+/// `krylov_dimension`. The Krylov vectors are fully
+/// reorthogonalized, and the Krylov space ends early if
+/// it becomes invariant under the Hamiltonian.
+pub fn evolve<T: Implemented>(
+    hamiltonian: &T,
+    state: &ComplexState,
+    sector: &Sector,
+    time: f32,
+    krylov_dimension: usize,
+) -> Result<ComplexState, Error> {
+    state.check_sector(sector)?;
+
+    let threads = sector.model.threads;
+    let norm = state.get_norm(sector)?;
+
+    if norm == 0.0 || !norm.is_finite() {
+        return Err(Error::VanishingNorm);
+    }
+
+    let mut krylov_states = vec![ComplexState {
+        real: &state.real * (1.0 / norm),
+        imaginary: &state.imaginary * (1.0 / norm),
+    }];
+    let mut tridiagonal = Tridiagonal {
+        diagonal: Vec::with_capacity(krylov_dimension),
+        off_diagonal: Vec::with_capacity(krylov_dimension),
+    };
+
+    loop {
+        let krylov_state = &krylov_states[krylov_states.len() - 1];
+        let mut next_state = apply(hamiltonian, krylov_state, sector)?;
+        tridiagonal.diagonal.push(krylov_state.dot(&next_state, sector)?.re as f64);
+
+        if krylov_states.len() >= krylov_dimension.max(1) {
+            break;
+        }
+
+        for krylov_state in krylov_states.iter() {
+            let overlap = krylov_state.dot(&next_state, sector)?;
+            next_state.subtract(krylov_state, overlap, threads)?;
+        }
+
+        let next_norm = next_state.get_norm(sector)?;
+
+        if next_norm < 1e-6 || !next_norm.is_finite() {
+            break;
+        }
+
+        next_state.real *= 1.0 / next_norm;
+        next_state.imaginary *= 1.0 / next_norm;
+        tridiagonal.off_diagonal.push(next_norm as f64);
+        krylov_states.push(next_state);
+    }
+
+    // exp(-iHt)|ψ⟩ = |ψ| Σ_j c_j |v_j⟩, where
+    // c_j = Σ_k U_jk exp(-iλ_k t) U_0k.
+    let (eigenvalues, eigenvectors) = tridiagonal.get_eigenpairs()?;
+    let mut evolved_state = ComplexState {
+        real: sector.from_zeros(),
+        imaginary: sector.from_zeros(),
+    };
+
+    for (index, krylov_state) in krylov_states.iter().enumerate() {
+        let coefficient: Complex32 = eigenvalues
+            .iter()
+            .zip(eigenvectors.iter())
+            .map(|(eigenvalue, eigenvector)| {
+                Complex32::from_polar(
+                    (eigenvector[index] * eigenvector[0]) as f32,
+                    -(*eigenvalue as f32) * time,
+                )
+            })
+            .sum();
+        evolved_state.subtract(krylov_state, -coefficient * norm, threads)?;
+    }
+
+    Ok(evolved_state)
+}
+
+/// Evolves a [`ComplexState`] of a [`Sector`] by
+/// `steps` time steps of length `time_step`, each in a
+/// Krylov space of dimension at most `krylov_dimension`.
+/// The norm, the energy and the observables are
+/// recorded at the start and after every step. The
+/// evolved [`ComplexState`] is returned together with
+/// the records.
+pub fn time_evolve<T: Implemented>(
+    hamiltonian: &T,
+    initial_state: ComplexState,
+    sector: &Sector,
+    time_step: f32,
+    steps: usize,
+    krylov_dimension: usize,
+    observables: &[ComplexObservable],
+) -> Result<(ComplexState, Vec<EvolutionPoint>), Error> {
+    let initial_norm = initial_state.get_norm(sector)?;
+    let initial_energy = get_energy(hamiltonian, &initial_state, sector)?;
+    let mut state = initial_state;
+    let mut evolution_points = Vec::with_capacity(steps + 1);
+
+    for step in 0..=steps {
+        if step > 0 {
+            state = evolve(hamiltonian, &state, sector, time_step, krylov_dimension)?;
+        }
+
+        let observables = observables
+            .iter()
+            .map(|observable| observable(&state, sector))
+            .collect::<Result<Vec<f32>, Error>>()?;
+
+        evolution_points.push(EvolutionPoint {
+            time: step as f32 * time_step,
+            norm_deviation: state.get_norm(sector)? - initial_norm,
+            energy_deviation: get_energy(hamiltonian, &state, sector)? - initial_energy,
+            observables,
+        });
+    }
+
+    Ok((state, evolution_points))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::basis::SzSector;
+    use crate::hamiltonians::{Hamiltonian, AFH};
+    use crate::model::Model;
+    use crate::testing::{get_eigenpairs, get_sector_matrix};
+
+    #[test]
+    fn krylov_evolution_agrees_with_exact_exponentiation() {
+        let hamiltonian = Hamiltonian::<AFH>::new(0.7);
+        let model = Model::new(3, 6, SzSector::new(0)).unwrap();
+        let plain_model = Model::new_plain(3, 6, Some(SzSector::new(0))).unwrap();
+        let plain_sector = Sector::trivial(&plain_model).unwrap();
+        let eigenpairs = get_eigenpairs(&get_sector_matrix(&hamiltonian, &plain_sector));
+        let time = 0.8;
+
+        for sector in Sector::get_all(&model).unwrap() {
+            let state = ComplexState::from_real(sector.from_random(1), &sector).unwrap();
+            let evolved_state = evolve(&hamiltonian, &state, &sector, time, 30).unwrap();
+            // The plain basis is orthonormal, so
+            // exp(-iHt)|ψ⟩ = Σ_k exp(-iλ_k t) ⟨u_k|ψ⟩ |u_k⟩.
+            let full_state = state.real.get_full_state(&sector).unwrap();
+            let initial_vector: Vec<f64> = plain_model.basis_states.representers
+                .iter()
+                .map(|value| full_state[*value] as f64)
+                .collect();
+            let full_real = evolved_state.real.get_full_state(&sector).unwrap();
+            let full_imaginary = evolved_state.imaginary.get_full_state(&sector).unwrap();
+            let mut expected = vec![Complex32::new(0.0, 0.0); initial_vector.len()];
+
+            for (eigenvalue, eigenvector) in eigenpairs.iter() {
+                let overlap: f64 = eigenvector.iter().zip(initial_vector.iter()).map(|(a, b)| a * b).sum();
+                let phase = Complex32::from_polar(overlap as f32, -(*eigenvalue as f32) * time);
+
+                for (element, eigenvector_element) in expected.iter_mut().zip(eigenvector.iter()) {
+                    *element += phase * *eigenvector_element as f32;
+                }
+            }
+
+            for (index, value) in plain_model.basis_states.representers.iter().enumerate() {
+                let found = Complex32::new(full_real[*value], full_imaginary[*value]);
+
+                assert!((found - expected[index]).norm() < 1e-4, "{:?}: {} != {}", sector.translation, found, expected[index]);
+            }
+        }
+    }
+
+    #[test]
+    fn eigenstates_only_pick_up_a_phase() {
+        let hamiltonian = Hamiltonian::<AFH>::new(1.0);
+        let model = Model::new(3, 6, SzSector::new(0)).unwrap();
+        let sector = Sector::trivial(&model).unwrap();
+        let (mut eigenstate, energy) = sector.find_eigenstate(&hamiltonian, 2000, &[]).unwrap();
+        eigenstate.normalize(&sector).unwrap();
+        let state = ComplexState::from_real(eigenstate, &sector).unwrap();
+
+        for time in [0.5, 2.0, 5.0] {
+            let evolved_state = evolve(&hamiltonian, &state, &sector, time, 20).unwrap();
+            let overlap = state.dot(&evolved_state, &sector).unwrap();
+
+            assert!((overlap - Complex32::from_polar(1.0, -energy * time)).norm() < 1e-3, "t {}: {}", time, overlap);
+        }
+    }
+
+    #[test]
+    fn evolution_conserves_the_norm_and_the_energy() {
+        let model = Model::new(3, 6, SzSector::new(0)).unwrap();
+        let sector = Sector::trivial(&model).unwrap();
+        let initial_state = ComplexState::from_real(sector.from_random(2), &sector).unwrap();
+        let (_, evolution_points) = time_evolve(&Hamiltonian::<AFH>::new(1.0), initial_state, &sector, 0.25, 20, 20, &[]).unwrap();
+
+        assert_eq!(evolution_points.len(), 21);
+
+        for evolution_point in evolution_points.iter() {
+            assert!(evolution_point.norm_deviation.abs() < 1e-4, "{:?}", evolution_point);
+            assert!(evolution_point.energy_deviation.abs() < 1e-4, "{:?}", evolution_point);
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,244 @@
+//! This module contains the ground state fidelity
+//! F = |⟨ψ(s)|ψ(s+δs)⟩| between the normalized ground
+//! states of neighbouring parameters, and the fidelity
+//! susceptibility χ_F = -2 ln(F)/δs^2, which locate phase
+//! transitions without an order parameter: χ_F peaks
+//! at a transition and grows with the chain length.
+use crate::error::Error;
+use crate::hamiltonians::Implemented;
+use crate::sector::Sector;
+use crate::states::State;
+use crate::sweep::sweep_ground_states;
+
+/// The fidelity between the ground states of two
+/// neighbouring parameters of a [`fidelity_sweep`].
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct FidelityPoint {
+    /// The midpoint of the two parameters.
+    pub parameter: f32,
+    /// The fidelity, or `None` if the ground states
+    /// belong to different sectors.
+    pub fidelity: Option<f32>,
+    /// The fidelity susceptibility, or `None` if the
+    /// ground states belong to different sectors.
+    pub fidelity_susceptibility: Option<f32>,
+}
+
+/// Gets the fidelity |⟨ψ_0|ψ_1⟩|/(|ψ_0| |ψ_1|) between two
+/// [`State`]s of a [`Sector`], using the inner product of
+/// the symmetrized basis.
+pub fn get_fidelity(
+    state_0: &State,
+    state_1: &State,
+    sector: &Sector,
+) -> Result<f32, Error> {
+    let norm_squared_0 = state_0.dot(state_0, sector)?;
+    let norm_squared_1 = state_1.dot(state_1, sector)?;
+    let norm = (norm_squared_0 * norm_squared_1).sqrt();
+
+    if norm == 0.0 || !norm.is_finite() {
+        return Err(Error::VanishingNorm);
+    }
+
+    Ok((state_0.dot(state_1, sector)?.abs() / norm).min(1.0))
+}
+
+/// Gets the fidelity susceptibility -2 ln(F)/δs^2 from
+/// the fidelity F between the ground states of parameters
+/// differing by δs. Dividing it by the length gives the
+/// fidelity susceptibility per site.
+pub fn get_fidelity_susceptibility(fidelity: f32, step: f32) -> f32 {
+    -2.0 * fidelity.ln() / (step * step)
+}
+
+/// Finds the ground state in the given sectors for every
+/// parameter by [`crate::sweep::sweep`], where
+/// `get_hamiltonian` creates the Hamiltonian of a
+/// parameter, and gets the fidelity between the ground
+/// states of neighbouring parameters. If the ground state
+/// moves to another sector, the fidelity and the fidelity
+/// susceptibility are `None`, since the ground states
+/// then differ in a symmetry eigenvalue and the crossing
+/// is not resolved by the step.
+pub fn fidelity_sweep<T, H>(
+    sectors: &[Sector],
+    parameters: &[f32],
+    get_hamiltonian: H,
+    initial_iterations: u32,
+    iterations: u32,
+) -> Result<Vec<FidelityPoint>, Error> where T: Implemented, H: Fn(f32) -> T {
+    let mut fidelity_points = Vec::with_capacity(parameters.len().saturating_sub(1));
+    let mut previous_ground_state: Option<(f32, usize, State)> = None;
+
+    sweep_ground_states(
+        sectors,
+        parameters,
+        get_hamiltonian,
+        1,
+        initial_iterations,
+        iterations,
+        |parameter, _, ground_state_sector_index, ground_state| {
+            if let Some((previous_parameter, previous_sector_index, previous_ground_state)) = previous_ground_state.take() {
+                let fidelity = if previous_sector_index == ground_state_sector_index {
+                    Some(get_fidelity(&previous_ground_state, ground_state, &sectors[ground_state_sector_index])?)
+                } else {
+                    None
+                };
+
+                fidelity_points.push(FidelityPoint {
+                    parameter: (previous_parameter + parameter) / 2.0,
+                    fidelity,
+                    fidelity_susceptibility: fidelity.map(|fidelity| {
+                        get_fidelity_susceptibility(fidelity, parameter - previous_parameter)
+                    }),
+                });
+            }
+
+            previous_ground_state = Some((parameter, ground_state_sector_index, ground_state.clone()));
+
+            Ok(())
+        },
+    )?;
+
+    Ok(fidelity_points)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::basis::SzSector;
+    use crate::hamiltonians::{apply_matrix_elements, Hamiltonian, MatrixElements, AFH};
+    use crate::model::{Model, ModelBuilder};
+    use crate::symmetries::Reflection;
+    use crate::testing::{get_eigenpairs, get_sector_matrix, Perturbed};
+
+    /// The Zeeman Hamiltonian h S^z_tot.
+    struct Zeeman {
+        field: f32,
+    }
+
+    impl MatrixElements for Zeeman {
+        fn get_matrix_elements(
+            &self,
+            value: usize,
+            digits: &[u8],
+            model: &Model,
+            matrix_elements: &mut Vec<(usize, f32)>,
+        ) {
+            let s_z: f32 = digits[..model.length as usize].iter().map(|digit| *digit as f32 - model.spin).sum();
+            matrix_elements.push((value, self.field * s_z));
+        }
+    }
+
+    impl Implemented for Zeeman {
+        fn apply(
+            &self,
+            input_state: &State,
+            output_state: &mut State,
+            sector: &Sector,
+        ) -> Result<(), Error> {
+            apply_matrix_elements(self, input_state, output_state, sector)
+        }
+
+        fn get_max_eigenenergy(&self, model: &Model) -> f32 {
+            self.field.abs() * model.spin * model.length as f32
+        }
+    }
+
+    #[test]
+    fn a_change_of_sector_has_no_fidelity() {
+        let positive_model = Model::new_plain(2, 4, Some(SzSector::new(1))).unwrap();
+        let negative_model = Model::new_plain(2, 4, Some(SzSector::new(-1))).unwrap();
+        let sectors = [Sector::trivial(&positive_model).unwrap(), Sector::trivial(&negative_model).unwrap()];
+        let fidelity_points = fidelity_sweep(
+            &sectors,
+            &[-1.0, -0.5, 0.5, 1.0],
+            |field| Zeeman {field},
+            100,
+            100,
+        ).unwrap();
+
+        assert_eq!(fidelity_points.len(), 3);
+        assert_eq!(fidelity_points[1].fidelity, None);
+        assert_eq!(fidelity_points[1].fidelity_susceptibility, None);
+
+        for index in [0, 2] {
+            let fidelity = fidelity_points[index].fidelity.unwrap();
+            assert!((fidelity - 1.0).abs() < 1e-4, "{:?}", fidelity_points[index]);
+        }
+    }
+    /// The S=1 AFH chain with the dimerization δ, whose
+    /// exchanges alternate between 1+δ and 1-δ.
+    fn get_dimerized_afh(dimerization: f32) -> Perturbed<Hamiltonian<AFH>> {
+        let mut hamiltonian = Perturbed::new(Hamiltonian::<AFH>::new(1.0));
+        hamiltonian.exchanges = (0..6).map(|bond| if bond % 2 == 0 {dimerization} else {-dimerization}).collect();
+
+        hamiltonian
+    }
+
+    #[test]
+    fn dimerization_agrees_with_brute_force_overlaps() {
+        // Dimerization breaks translation, but not the
+        // bond-centered reflection.
+        let model = ModelBuilder::new(3, 6)
+            .total_s_z(Some(SzSector::new(0)))
+            .time_reversal(true)
+            .reflection(Some(Reflection::Bond))
+            .build()
+            .unwrap();
+        let sectors = Sector::get_all(&model).unwrap();
+        let step = 0.1;
+        let parameters: Vec<f32> = (0..=7).map(|index| index as f32 * step).collect();
+        let fidelity_points = fidelity_sweep(&sectors, &parameters, get_dimerized_afh, 1000, 300).unwrap();
+        // The ground state and its sector by diagonalizing
+        // the matrix of every sector, whose basis is
+        // orthonormal.
+        let get_ground_state = |dimerization: f32| {
+            let hamiltonian = get_dimerized_afh(dimerization);
+            let mut eigenpairs: Vec<(f64, usize, Vec<f64>)> = sectors
+                .iter()
+                .enumerate()
+                .flat_map(|(sector_index, sector)| {
+                    get_eigenpairs(&get_sector_matrix(&hamiltonian, sector))
+                        .into_iter()
+                        .map(move |(eigenvalue, eigenvector)| (eigenvalue, sector_index, eigenvector))
+                })
+                .collect();
+            eigenpairs.sort_by(|eigenpair_0, eigenpair_1| eigenpair_0.0.total_cmp(&eigenpair_1.0));
+            assert!(eigenpairs[1].0 - eigenpairs[0].0 > 1e-2, "δ {}: degenerate ground state", dimerization);
+
+            eigenpairs.swap_remove(0)
+        };
+        let mut ground_states = parameters.iter().map(|parameter| get_ground_state(*parameter));
+        let (_, mut previous_sector_index, mut previous_ground_state) = ground_states.next().unwrap();
+
+        for (fidelity_point, (_, sector_index, ground_state)) in fidelity_points.iter().zip(ground_states) {
+            assert_eq!(fidelity_point.fidelity.is_some(), sector_index == previous_sector_index);
+            let expected_fidelity = previous_ground_state
+                .iter()
+                .zip(ground_state.iter())
+                .map(|(coefficient_0, coefficient_1)| coefficient_0 * coefficient_1)
+                .sum::<f64>()
+                .abs() as f32;
+            let expected_susceptibility = get_fidelity_susceptibility(expected_fidelity, step);
+            let fidelity = fidelity_point.fidelity.unwrap();
+            let susceptibility = fidelity_point.fidelity_susceptibility.unwrap();
+
+            assert!((fidelity - expected_fidelity).abs() < 1e-4, "{:?}: {}", fidelity_point, expected_fidelity);
+            assert!((susceptibility - expected_susceptibility).abs() < 0.05 * expected_susceptibility + 0.05);
+
+            previous_sector_index = sector_index;
+            previous_ground_state = ground_state;
+        }
+
+        // The fidelity susceptibility peaks between the
+        // Haldane and the dimerized phase.
+        let susceptibilities: Vec<f32> = fidelity_points
+            .iter()
+            .map(|fidelity_point| fidelity_point.fidelity_susceptibility.unwrap())
+            .collect();
+        let peak = susceptibilities.iter().copied().fold(0.0, f32::max);
+
+        assert!(peak > 1.5 * susceptibilities[0] && peak > 1.5 * susceptibilities[susceptibilities.len() - 1]);
+    }
+}
\ No newline at end of file
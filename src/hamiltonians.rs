@@ -1,23 +1,162 @@
 //! This module contains the objects needed to define 
 //! a Hamiltonian. The Hamiltonians [`AFH`] and 
 //! [`AKLT`] are already implemented.
+use crate::basis::write_digits;
+use crate::error::Error;
+use crate::parallel::for_each_chunk;
 use crate::states::State;
 use crate::model::Model;
+use crate::sector::Sector;
+use crate::symmetries::SymmetryFactors;
 
 /// Defines the functionalities of a [`Hamiltonian`].
 pub trait Implemented {
-    /// Applying an operator.
+    /// Applying an operator in a [`Sector`], adding the 
+    /// result to the output state, which is then tagged 
+    /// with the [`Sector`]. Operators given by their 
+    /// matrix elements can use [`apply_matrix_elements`].
     fn apply(
         &self,
         input_state: &State,
         output_state: &mut State,
-        model: &Model,
-        symmetry_factors: &Vec<f32>,
-    );
+        sector: &Sector,
+    ) -> Result<(), Error>;
     /// Gets the largest eigenvalue of an operator.
     fn get_max_eigenenergy(&self, model: &Model) -> f32;
 }
 
+/// Defines the matrix elements of a [`Hamiltonian`],
+/// which are needed to apply it with several threads
+/// and by the diagnostics.
+pub trait MatrixElements: Implemented + Sync {
+    /// Gets the matrix elements of an operator, that is
+    /// the basis states reached from a basis state 
+    /// together with the corresponding coefficients.
+    /// The digits of the basis state are given with the
+    /// first digit repeated at the end.
+    /// The operator must be real and symmetric.
+    fn get_matrix_elements(
+        &self,
+        value: usize,
+        digits: &[u8],
+        model: &Model,
+        matrix_elements: &mut Vec<(usize, f32)>,
+    );
+}
+
+/// Applies an operator in a [`Sector`] by its matrix 
+/// elements, adding the result to the output state, 
+/// which is then tagged with the [`Sector`]. With more 
+/// than one thread, every thread gathers a contiguous 
+/// chunk of the coefficients of the output state.
+pub fn apply_matrix_elements<T: MatrixElements>(
+    operator: &T,
+    input_state: &State,
+    output_state: &mut State,
+    sector: &Sector,
+) -> Result<(), Error> {
+    input_state.check_sector(sector)?;
+    output_state.check_sector(sector)?;
+
+    let model = sector.model;
+    let symmetry_factors = &sector.symmetry_factors;
+
+    if model.threads <= 1 {
+        scatter(operator, input_state, output_state, model, symmetry_factors)
+    } else {
+        gather(operator, input_state, output_state, model, symmetry_factors)
+    }
+
+    output_state.sector = Some(sector.id);
+
+    Ok(())
+}
+
+/// Applies an operator by scattering the contribution 
+/// of every representer onto the output state.
+fn scatter<T: MatrixElements>(
+    operator: &T,
+    input_state: &State,
+    output_state: &mut State,
+    model: &Model,
+    symmetry_factors: &SymmetryFactors,
+) {
+    let mut matrix_elements = Vec::new();
+    let mut digits = Vec::with_capacity(model.length as usize + 1);
+
+    for (basis_state_index, representer_value) in model.basis_states.representers.iter().enumerate() {
+        let symmetry_factor = symmetry_factors.factors[basis_state_index];
+
+        if symmetry_factor == 0.0 {
+            continue;
+        }
+
+        let coefficient = input_state.coefficients[basis_state_index];
+        write_digits(*representer_value, model.base, model.length, &mut digits);
+        matrix_elements.clear();
+        operator.get_matrix_elements(*representer_value, &digits, model, &mut matrix_elements);
+
+        for (new_value, matrix_element) in matrix_elements.iter() {
+            let Some((new_basis_state_index, symmetry_sign)) = model.find_representer(*new_value) else {
+                continue;
+            };
+            let new_symmetry_factor = symmetry_factors.factors[new_basis_state_index];
+            let symmetry_ratio = symmetry_factors.get_sign(symmetry_sign) * new_symmetry_factor / symmetry_factor;
+
+            output_state.coefficients[new_basis_state_index] += coefficient
+                * matrix_element
+                * symmetry_ratio;
+        }
+    }
+}
+
+/// Applies an operator by gathering the contributions 
+/// to every coefficient of the output state. Since the 
+/// operator is symmetric, the matrix elements of a row 
+/// are found by applying it to the representer of the row.
+fn gather<T: MatrixElements>(
+    operator: &T,
+    input_state: &State,
+    output_state: &mut State,
+    model: &Model,
+    symmetry_factors: &SymmetryFactors,
+) {
+    for_each_chunk(&mut output_state.coefficients, model.threads, |offset, coefficients| {
+        let mut matrix_elements = Vec::new();
+        let mut digits = Vec::with_capacity(model.length as usize + 1);
+
+        for (index, output_coefficient) in coefficients.iter_mut().enumerate() {
+            let basis_state_index = offset + index;
+            let representer_value = model.basis_states.representers[basis_state_index];
+
+            if symmetry_factors.factors[basis_state_index] == 0.0 {
+                continue;
+            }
+
+            let mut coefficient = 0.0;
+            write_digits(representer_value, model.base, model.length, &mut digits);
+            matrix_elements.clear();
+            operator.get_matrix_elements(representer_value, &digits, model, &mut matrix_elements);
+
+            for (new_value, matrix_element) in matrix_elements.iter() {
+                let Some((new_basis_state_index, symmetry_sign)) = model.find_representer(*new_value) else {
+                    continue;
+                };
+
+                if symmetry_factors.factors[new_basis_state_index] == 0.0 {
+                    continue;
+                }
+
+                coefficient += input_state.coefficients[new_basis_state_index]
+                    * matrix_element
+                    * symmetry_factors.get_sign(symmetry_sign);
+            }
+
+            *output_coefficient += coefficient;
+        }
+    });
+}
+
 /// Represents a Hamiltonian operator.
 pub struct Hamiltonian<T> {
     /// The parameters of the Hamiltonian.
@@ -62,128 +201,115 @@ impl Hamiltonian<AKLT> {
     }
 }
 
-impl Implemented for Hamiltonian<AFH> {
-    /// Applies the AFH Hamiltonian.
-    fn apply(
+impl MatrixElements for Hamiltonian<AFH> {
+    /// Gets the matrix elements of the AFH Hamiltonian.
+    fn get_matrix_elements(
         &self,
-        input_state: &State,
-        output_state: &mut State,
+        value: usize,
+        digits: &[u8],
         model: &Model,
-        symmetry_factors: &Vec<f32>
+        matrix_elements: &mut Vec<(usize, f32)>,
     ) {
-        for basis_state_index in 0..model.basis_states.length {
-            let representer = &model.basis_states.representers[basis_state_index];
-            let symmetry_factor = symmetry_factors[representer.value];
+        let mut trivial_eigenvalue = 0.0;
 
-            if symmetry_factor == 0.0 {
-                continue;
-            }
+        for digit in digits[..model.length as usize].iter() {
+            let sigma = *digit as f32 - model.spin;
 
-            let coefficient = input_state.coefficients[basis_state_index];
-            let mut trivial_eigenvalue = 0.0;
-
-            for chain_index in 0..model.length as usize {
-                let digit = representer.digits[chain_index];
-                let sigma = representer.sigmas[chain_index];
-                let next_digit = representer.digits[chain_index + 1];
-                let next_sigma = representer.sigmas[chain_index + 1];
-
-                trivial_eigenvalue += sigma * (
-                    next_sigma * self.parameters.s
-                    + sigma * self.parameters.one_minus_s
-                );
-
-                if (digit != 0) && (next_digit != model.base - 1) {
-                    let new_representer_value = (representer.value as isize + model.flippers[chain_index]) as usize;
-                    let new_basis_state_index = model.basis_states.representer_map[new_representer_value];
-                    let new_symmetry_factor = symmetry_factors[new_representer_value];
-                    let symmetry_ratio = new_symmetry_factor / symmetry_factor;
-                    let mp_coefficient = model.m_coefficients[digit as usize] * model.p_coefficients[next_digit as usize];
-
-                    output_state.coefficients[new_basis_state_index] += coefficient 
-                        * mp_coefficient
-                        * symmetry_ratio
-                        * self.parameters.s;
-                }
+            trivial_eigenvalue += sigma * sigma * self.parameters.one_minus_s;
+        }
 
-                if (digit != model.base - 1) && (next_digit != 0) {
-                    let new_representer_value = (representer.value as isize - model.flippers[chain_index]) as usize;
-                    let new_basis_state_index = model.basis_states.representer_map[new_representer_value];
-                    let new_symmetry_factor = symmetry_factors[new_representer_value];
-                    let symmetry_ratio = new_symmetry_factor / symmetry_factor;
-                    let pm_coefficient = model.p_coefficients[digit as usize] * model.m_coefficients[next_digit as usize];
-
-                    output_state.coefficients[new_basis_state_index] += coefficient
-                        * pm_coefficient
-                        * symmetry_ratio
-                        * self.parameters.s;
-                }
+        for chain_index in 0..model.bonds {
+            let digit = digits[chain_index];
+            let sigma = digit as f32 - model.spin;
+            let next_digit = digits[chain_index + 1];
+            let next_sigma = next_digit as f32 - model.spin;
+
+            trivial_eigenvalue += sigma * next_sigma * self.parameters.s;
+
+            if (digit != 0) && (next_digit != model.base - 1) {
+                let new_value = (value as isize + model.flippers[chain_index]) as usize;
+                let mp_coefficient = model.m_coefficients[digit as usize] * model.p_coefficients[next_digit as usize];
+
+                matrix_elements.push((new_value, mp_coefficient * self.parameters.s));
             }
 
-            output_state.coefficients[basis_state_index] += coefficient * trivial_eigenvalue
+            if (digit != model.base - 1) && (next_digit != 0) {
+                let new_value = (value as isize - model.flippers[chain_index]) as usize;
+                let pm_coefficient = model.p_coefficients[digit as usize] * model.m_coefficients[next_digit as usize];
+
+                matrix_elements.push((new_value, pm_coefficient * self.parameters.s));
+            }
         }
-    }
 
-    fn get_max_eigenenergy(&self, model: &Model) -> f32 {
-        model.spin.powi(2) * model.length as f32
+        matrix_elements.push((value, trivial_eigenvalue))
     }
 }
 
-impl Implemented for Hamiltonian<AKLT> {
-    /// Applies the AKLT Hamiltonian.
+impl Implemented for Hamiltonian<AFH> {
+    /// Applies the AFH Hamiltonian.
     fn apply(
         &self,
         input_state: &State,
         output_state: &mut State,
+        sector: &Sector,
+    ) -> Result<(), Error> {
+        apply_matrix_elements(self, input_state, output_state, sector)
+    }
+
+    fn get_max_eigenenergy(&self, model: &Model) -> f32 {
+        model.spin.powi(2) * model.length as f32
+    }
+}
+
+impl MatrixElements for Hamiltonian<AKLT> {
+    /// Gets the matrix elements of the AKLT Hamiltonian.
+    fn get_matrix_elements(
+        &self,
+        value: usize,
+        digits: &[u8],
         model: &Model,
-        symmetry_factors: &Vec<f32>
+        matrix_elements: &mut Vec<(usize, f32)>,
     ) {
-        for basis_state_index in 0..model.basis_states.length {
-            let representer = &model.basis_states.representers[basis_state_index];
-            let symmetry_factor = symmetry_factors[representer.value];
+        let trivial_eigenvalue: f32 = digits[..model.length as usize]
+            .iter()
+            .map(|digit| (*digit as f32 - model.spin).powi(2))
+            .sum();
 
-            if symmetry_factor == 0.0 {
-                continue;
-            }
+        for chain_index in 0..model.bonds {
+            let flippers = project_2(
+                digits[chain_index],
+                digits[chain_index + 1],
+                model.flippers[chain_index],
+            );
 
-            let coefficient = input_state.coefficients[basis_state_index];
-            let mut trivial_eigenvalue = 0.0;
-
-            for chain_index in 0..model.length as usize {
-                let flippers = project_2(
-                    representer.digits[chain_index],
-                    representer.digits[chain_index + 1],
-                    model.flippers[chain_index],
-                );
-
-                trivial_eigenvalue += representer.sigmas[chain_index].powi(2);
-                
-                for (sign, flipper, cg_coefficient) in flippers {
-                    if cg_coefficient != 0.0 {
-                        let new_representer_value = if sign {
-                            (representer.value as isize - flipper) as usize
-                        } else {
-                            (representer.value as isize + flipper) as usize
-                        };
-
-                        let new_basis_state_index = model.basis_states.representer_map[new_representer_value];
-                        let new_symmetry_factor = symmetry_factors[new_representer_value];
-                        let symmetry_ratio = new_symmetry_factor / symmetry_factor;
-
-                        output_state.coefficients[new_basis_state_index] += coefficient
-                            * cg_coefficient
-                            * symmetry_ratio
-                            * self.parameters.s;
+            for (sign, flipper, cg_coefficient) in flippers {
+                if cg_coefficient != 0.0 {
+                    let new_value = if sign {
+                        (value as isize - flipper) as usize
                     } else {
-                        break;
-                    }
+                        (value as isize + flipper) as usize
+                    };
+
+                    matrix_elements.push((new_value, cg_coefficient * self.parameters.s));
+                } else {
+                    break;
                 }
             }
-
-            output_state.coefficients[basis_state_index] += coefficient 
-                * trivial_eigenvalue
-                * self.parameters.one_minus_s;
         }
+
+        matrix_elements.push((value, trivial_eigenvalue * self.parameters.one_minus_s))
+    }
+}
+
+impl Implemented for Hamiltonian<AKLT> {
+    /// Applies the AKLT Hamiltonian.
+    fn apply(
+        &self,
+        input_state: &State,
+        output_state: &mut State,
+        sector: &Sector,
+    ) -> Result<(), Error> {
+        apply_matrix_elements(self, input_state, output_state, sector)
     }
 
     fn get_max_eigenenergy(&self, model: &Model) -> f32 {
@@ -214,19 +340,19 @@ fn project_2(
             (false, 0, 0.0)
         ],
         (2, 0) => [ // |+-⟩
-            (false, 0, 0.166666666), // 1/6*|+-⟩
-            (false, flipper, 0.33333333), // 1/3*|00⟩
-            (false, 2*flipper, 0.166666666) // 1/6*|-+⟩
+            (false, 0, 0.16666667), // 1/6*|+-⟩
+            (false, flipper, 0.33333334), // 1/3*|00⟩
+            (false, 2*flipper, 0.16666667) // 1/6*|-+⟩
         ],
         (1, 1) => [ // |00⟩
             (false, 0, 0.6666666), // 1/6*|00⟩
-            (true, flipper, 0.33333333), // 1/3*|+-⟩
-            (false, flipper, 0.33333333) // 1/3*|-+⟩
+            (true, flipper, 0.33333334), // 1/3*|+-⟩
+            (false, flipper, 0.33333334) // 1/3*|-+⟩
         ],
         (0, 2) => [ // |-+⟩
-            (false, 0, 0.166666666), // 1/6*|-+⟩
-            (true, flipper, 0.33333333), // 1/3*|00⟩
-            (true, 2*flipper, 0.166666666) // 1/6*|+-⟩
+            (false, 0, 0.16666667), // 1/6*|-+⟩
+            (true, flipper, 0.33333334), // 1/3*|00⟩
+            (true, 2*flipper, 0.16666667) // 1/6*|+-⟩
         ],
         (2, 1) => [ // |+0⟩
             (false, 0, 0.5), // 1/2*|+0⟩
@@ -251,4 +377,69 @@ fn project_2(
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::basis::SzSector;
+    use crate::model::{Model, ModelBuilder};
+    use crate::symmetries::{Momentum, Parity, Reflection};
+
+    /// Applies the AFH Hamiltonian to a random [`State`] of
+    /// a sector with a given number of threads.
+    fn apply_with_threads(model_builder: ModelBuilder, threads: usize) -> Vec<f32> {
+        let model = model_builder.threads(threads).build().unwrap();
+        let sector = Sector::new(&model, Parity::Even, Parity::Odd, Momentum::Pi).unwrap();
+        let input_state = sector.from_random(7);
+        let mut output_state = sector.from_zeros();
+        Hamiltonian::<AFH>::new(0.8).apply(&input_state, &mut output_state, &sector).unwrap();
 
+        output_state.coefficients
+    }
+
+    #[test]
+    fn threads_give_the_same_result() {
+        let model_builders = [
+            ModelBuilder::new(3, 8)
+                .total_s_z(Some(SzSector::new(0)))
+                .time_reversal(true)
+                .reflection(Some(Reflection::Bond))
+                .translation(true),
+            ModelBuilder::new(2, 10).total_s_z(Some(SzSector::new(1))),
+            ModelBuilder::new(3, 6),
+        ];
+
+        for model_builder in model_builders {
+            let expected = apply_with_threads(model_builder, 1);
+
+            for threads in [2, 3, 8] {
+                let found = apply_with_threads(model_builder, threads);
+                assert_eq!(found.len(), expected.len());
+
+                for (found, expected) in found.iter().zip(expected.iter()) {
+                    assert!((found - expected).abs() < 1e-5, "{} threads: {} != {}", threads, found, expected);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn threads_give_the_same_inner_product() {
+        let model = Model::new(3, 8, SzSector::new(0)).unwrap();
+        let sector = Sector::trivial(&model).unwrap();
+        let state_0 = sector.from_random(1);
+        let state_1 = sector.from_random(2);
+        let expected = state_0.dot(&state_1, &sector).unwrap();
+        let threaded_model = ModelBuilder::new(3, 8)
+            .total_s_z(Some(SzSector::new(0)))
+            .time_reversal(true)
+            .reflection(Some(Reflection::Bond))
+            .translation(true)
+            .threads(4)
+            .build()
+            .unwrap();
+        let threaded_sector = Sector::trivial(&threaded_model).unwrap();
+        let found = state_0.dot(&state_1, &threaded_sector).unwrap();
+
+        assert!((found - expected).abs() < 1e-4 * expected.abs().max(1.0));
+    }
+}
\ No newline at end of file
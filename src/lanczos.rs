@@ -0,0 +1,266 @@
+//! This module contains the tools of the Lanczos
+//! algorithm, which reduces a Hamiltonian to a symmetric
+//! tridiagonal matrix in the Krylov space spanned by
+//! repeated applications to a [`crate::states::State`].
+//! The small tridiagonal matrices are diagonalized in
+//! double precision.
+use crate::error::Error;
+use crate::evolution::{apply, ComplexState};
+use crate::hamiltonians::Implemented;
+use crate::sector::Sector;
+use num_complex::Complex64;
+
+/// A symmetric tridiagonal matrix.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Tridiagonal {
+    /// The diagonal elements α_j.
+    pub diagonal: Vec<f64>,
+    /// The off-diagonal elements β_j, which couple j and
+    /// j+1. There is one less than there are diagonal
+    /// elements.
+    pub off_diagonal: Vec<f64>,
+}
+
+impl Tridiagonal {
+    /// Gets the dimension of the matrix.
+    pub fn get_dimension(&self) -> usize {
+        self.diagonal.len()
+    }
+
+    /// Gets the diagonal element ⟨v_0|(z-T)^-1|v_0⟩ of the
+    /// resolvent by the continued fraction
+    /// 1/(z-α_0-β_0^2/(z-α_1-β_1^2/(z-α_2-...))).
+    pub fn get_resolvent(&self, z: Complex64) -> Complex64 {
+        let mut resolvent = Complex64::new(0.0, 0.0);
+
+        for (index, diagonal_element) in self.diagonal.iter().enumerate().rev() {
+            let off_diagonal_element = self.off_diagonal.get(index).copied().unwrap_or(0.0);
+            resolvent = 1.0 / (z - diagonal_element - off_diagonal_element * off_diagonal_element * resolvent);
+        }
+
+        resolvent
+    }
+
+    /// Gets the eigenvalues in increasing order and the
+    /// corresponding normalized eigenvectors, using the
+    /// QL algorithm with implicit shifts. An error is
+    /// returned if an eigenvalue has not converged after
+    /// 64 iterations.
+    pub fn get_eigenpairs(&self) -> Result<(Vec<f64>, Vec<Vec<f64>>), Error> {
+        let dimension = self.get_dimension();
+        let mut eigenvalues = self.diagonal.clone();
+        let mut off_diagonal: Vec<f64> = self.off_diagonal
+            .iter()
+            .copied()
+            .chain(std::iter::repeat(0.0))
+            .take(dimension)
+            .collect();
+        // The eigenvectors are the columns.
+        let mut eigenvectors: Vec<Vec<f64>> = (0..dimension)
+            .map(|row| (0..dimension).map(|column| if row == column {1.0} else {0.0}).collect())
+            .collect();
+
+        for start in 0..dimension {
+            let mut converged = false;
+
+            for _ in 0..64 {
+                // Finds the first negligible off-diagonal
+                // element, which splits the matrix.
+                let mut end = start;
+
+                while end + 1 < dimension {
+                    let scale = eigenvalues[end].abs() + eigenvalues[end + 1].abs();
+
+                    if off_diagonal[end].abs() <= f64::EPSILON * scale {
+                        break;
+                    }
+
+                    end += 1;
+                }
+
+                if end == start {
+                    converged = true;
+                    break;
+                }
+
+                let mut g = (eigenvalues[start + 1] - eigenvalues[start]) / (2.0 * off_diagonal[start]);
+                let mut r = g.hypot(1.0);
+                g = eigenvalues[end] - eigenvalues[start] + off_diagonal[start] / (g + r.copysign(g));
+                let (mut s, mut c, mut p) = (1.0, 1.0, 0.0);
+                let mut deflated = false;
+
+                for index in (start..end).rev() {
+                    let f = s * off_diagonal[index];
+                    let b = c * off_diagonal[index];
+                    r = f.hypot(g);
+                    off_diagonal[index + 1] = r;
+
+                    if r == 0.0 {
+                        eigenvalues[index + 1] -= p;
+                        off_diagonal[end] = 0.0;
+                        deflated = true;
+                        break;
+                    }
+
+                    s = f / r;
+                    c = g / r;
+                    g = eigenvalues[index + 1] - p;
+                    r = (eigenvalues[index] - g) * s + 2.0 * c * b;
+                    p = s * r;
+                    eigenvalues[index + 1] = g + p;
+                    g = c * r - b;
+
+                    for row in eigenvectors.iter_mut() {
+                        let f = row[index + 1];
+                        row[index + 1] = s * row[index] + c * f;
+                        row[index] = c * row[index] - s * f;
+                    }
+                }
+
+                if !deflated {
+                    eigenvalues[start] -= p;
+                    off_diagonal[start] = g;
+                    off_diagonal[end] = 0.0;
+                }
+            }
+
+            if !converged {
+                return Err(Error::NoConvergence);
+            }
+        }
+
+        let mut order: Vec<usize> = (0..dimension).collect();
+        order.sort_by(|index_0, index_1| eigenvalues[*index_0].total_cmp(&eigenvalues[*index_1]));
+
+        Ok((
+            order.iter().map(|index| eigenvalues[*index]).collect(),
+            order.iter().map(|index| eigenvectors.iter().map(|row| row[*index]).collect()).collect(),
+        ))
+    }
+}
+
+/// Runs at most `steps` steps of the Lanczos recursion
+/// β_{j+1}|v_{j+1}⟩ = H|v_j⟩ - α_j|v_j⟩ - β_j|v_{j-1}⟩,
+/// starting from the normalized [`ComplexState`]. Only
+/// the last two Lanczos vectors are kept, so they are not
+/// reorthogonalized, which does not affect the lowest
+/// eigenvalues and the continued fractions. The recursion
+/// ends early if the Krylov space becomes invariant.
+pub fn get_lanczos_coefficients<T: Implemented>(
+    hamiltonian: &T,
+    initial_state: &ComplexState,
+    sector: &Sector,
+    steps: usize,
+) -> Result<Tridiagonal, Error> {
+    initial_state.check_sector(sector)?;
+
+    let threads = sector.model.threads;
+    let norm = initial_state.get_norm(sector)?;
+
+    if norm == 0.0 || !norm.is_finite() {
+        return Err(Error::VanishingNorm);
+    }
+
+    let mut previous_state: Option<ComplexState> = None;
+    let mut state = ComplexState {
+        real: &initial_state.real * (1.0 / norm),
+        imaginary: &initial_state.imaginary * (1.0 / norm),
+    };
+    let mut tridiagonal = Tridiagonal {
+        diagonal: Vec::with_capacity(steps),
+        off_diagonal: Vec::with_capacity(steps),
+    };
+
+    for step in 0..steps.max(1) {
+        let mut next_state = apply(hamiltonian, &state, sector)?;
+        let diagonal_element = state.dot(&next_state, sector)?.re;
+        tridiagonal.diagonal.push(diagonal_element as f64);
+
+        if step + 1 == steps.max(1) {
+            break;
+        }
+
+        next_state.subtract(&state, diagonal_element.into(), threads)?;
+
+        if let (Some(previous_state), Some(off_diagonal_element)) = (&previous_state, tridiagonal.off_diagonal.last()) {
+            next_state.subtract(previous_state, (*off_diagonal_element as f32).into(), threads)?;
+        }
+
+        let next_norm = next_state.get_norm(sector)?;
+
+        if next_norm < 1e-6 || !next_norm.is_finite() {
+            break;
+        }
+
+        next_state.real *= 1.0 / next_norm;
+        next_state.imaginary *= 1.0 / next_norm;
+        tridiagonal.off_diagonal.push(next_norm as f64);
+        previous_state = Some(std::mem::replace(&mut state, next_state));
+    }
+
+    Ok(tridiagonal)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::PI;
+
+    /// Gets the tridiagonal matrix of the free chain of a
+    /// given length, whose eigenvalues are 2cos(kπ/(n+1)).
+    fn get_free_chain(dimension: usize) -> Tridiagonal {
+        Tridiagonal {
+            diagonal: vec![0.0; dimension],
+            off_diagonal: vec![1.0; dimension - 1],
+        }
+    }
+
+    #[test]
+    fn free_chain_eigenvalues_are_exact() {
+        for dimension in [1, 2, 5, 16, 40] {
+            let (eigenvalues, _) = get_free_chain(dimension).get_eigenpairs().unwrap();
+            let mut expected: Vec<f64> = (1..=dimension)
+                .map(|k| 2.0 * (k as f64 * PI / (dimension as f64 + 1.0)).cos())
+                .collect();
+            expected.sort_by(f64::total_cmp);
+
+            for (eigenvalue, expected_eigenvalue) in eigenvalues.iter().zip(expected.iter()) {
+                assert!((eigenvalue - expected_eigenvalue).abs() < 1e-12, "dimension {}", dimension);
+            }
+        }
+    }
+
+    #[test]
+    fn eigenvectors_are_orthonormal_eigenvectors() {
+        let tridiagonal = Tridiagonal {
+            diagonal: vec![1.0, -2.0, 0.5, 3.0, 0.0, -1.5],
+            off_diagonal: vec![0.7, 1.3, -0.4, 2.0, 0.9],
+        };
+        let dimension = tridiagonal.get_dimension();
+        let (eigenvalues, eigenvectors) = tridiagonal.get_eigenpairs().unwrap();
+
+        for (eigenvalue, eigenvector) in eigenvalues.iter().zip(eigenvectors.iter()) {
+            for row in 0..dimension {
+                let mut product = tridiagonal.diagonal[row] * eigenvector[row];
+
+                if row > 0 {
+                    product += tridiagonal.off_diagonal[row - 1] * eigenvector[row - 1];
+                }
+
+                if row + 1 < dimension {
+                    product += tridiagonal.off_diagonal[row] * eigenvector[row + 1];
+                }
+
+                assert!((product - eigenvalue * eigenvector[row]).abs() < 1e-12);
+            }
+        }
+
+        for (index_0, eigenvector_0) in eigenvectors.iter().enumerate() {
+            for (index_1, eigenvector_1) in eigenvectors.iter().enumerate() {
+                let overlap: f64 = eigenvector_0.iter().zip(eigenvector_1.iter()).map(|(a, b)| a * b).sum();
+                let expected_overlap = if index_0 == index_1 {1.0} else {0.0};
+                assert!((overlap - expected_overlap).abs() < 1e-12);
+            }
+        }
+    }
+}
\ No newline at end of file
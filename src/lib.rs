@@ -7,9 +7,31 @@
 //! - are reflection invariant.
 //! - are translation invariant.
 //! 
-//! See the [``examples``](https://github.com/emil-aagaard/quantum-spin-chains/tree/main/examples) folder for calculation of the ground state energies and spectral gaps of the S=1 antiferromagnetic Heisenberg (AFH) chain and the Affleck-Kennedy-Lieb-Tasaki (AKLT) chain.
+//! Each of these symmetries can be left out, so Hamiltonians with fields, disorder or open boundaries can be diagonalized in the plain basis of a sector of the total quantum spin in the z-direction, or in the full basis.
+//! 
+//! See the [``examples``](https://github.com/emil-aagaard/quantum-spin-chains/tree/main/examples) folder for calculation of the ground state energies and spectral gaps of the S=1 antiferromagnetic Heisenberg (AFH) chain and the Affleck-Kennedy-Lieb-Tasaki (AKLT) chain. Further examples treat the S=1 AFH chain with random fields, its magnetization curve, its dynamical structure factor, its thermodynamics and its extrapolation to infinite length, the CFT data and a quench from the Néel state of the critical S=1/2 AFH chain, the π-rotation eigenvalues of the edge states of the open AKLT chain, and a sweep from the trivial phase to the AKLT chain together with the fidelity susceptibility along it.
+pub mod error;
 pub mod symmetries;
+pub mod configurations;
 pub mod basis;
+pub mod spin_half;
 pub mod states;
 pub mod hamiltonians;
-pub mod model;
\ No newline at end of file
+pub mod model;
+pub mod sector;
+pub mod diagnostics;
+pub mod operators;
+pub mod spt;
+pub mod observables;
+pub mod sweep;
+pub mod scaling;
+pub mod cft;
+pub mod magnetization;
+pub mod fidelity;
+pub mod lanczos;
+pub mod evolution;
+pub mod structure_factor;
+pub mod thermodynamics;
+mod parallel;
+#[cfg(test)]
+mod testing;
\ No newline at end of file
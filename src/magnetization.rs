@@ -0,0 +1,205 @@
+//! This module contains the magnetization curve of a
+//! chain in a magnetic field h, whose Hamiltonian is
+//! H - h S^z_tot. Since H conserves S^z, the ground state
+//! energy in the field is the smallest E(S^z) - h S^z,
+//! where E(S^z) is the lowest energy of the sector S^z.
+//! The magnetization curve is therefore a staircase
+//! given by the lower convex hull of the points
+//! (S^z, E(S^z)), whose slopes are the fields at which
+//! the magnetization jumps.
+use crate::basis::SzSector;
+use crate::cft::get_sector_energies;
+use crate::error::Error;
+use crate::hamiltonians::Implemented;
+use crate::model::ModelBuilder;
+
+/// A jump of the magnetization curve.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct MagnetizationStep {
+    /// The field at which the magnetization jumps.
+    pub field: f32,
+    /// The magnetization per site above the field.
+    pub magnetization: f32,
+}
+
+/// The magnetization curve of a chain.
+#[derive(Clone, PartialEq, Debug)]
+pub struct MagnetizationCurve {
+    pub length: u8,
+    /// The lowest energy of every sector from the
+    /// smallest nonnegative S^z to S*length, as pairs
+    /// (S^z, E(S^z)). The sectors -S^z have the same
+    /// energies, since time reversal maps S^z to -S^z.
+    pub energies: Vec<(f32, f32)>,
+    /// The magnetization per site without field.
+    pub initial_magnetization: f32,
+    /// The jumps of the magnetization in increasing field.
+    pub steps: Vec<MagnetizationStep>,
+}
+
+impl MagnetizationCurve {
+    /// Gets the magnetization per site at a field.
+    pub fn get_magnetization(&self, field: f32) -> f32 {
+        self.steps
+            .iter()
+            .take_while(|step| step.field <= field)
+            .last()
+            .map(|step| step.magnetization)
+            .unwrap_or(self.initial_magnetization)
+    }
+
+    /// Gets the field at which the magnetization first
+    /// increases, which closes the gap of a gapped chain
+    /// such as the S=1 AFH chain, whose magnetization
+    /// vanishes below it (the Haldane plateau).
+    pub fn get_critical_field(&self) -> Option<f32> {
+        self.steps.first().map(|step| step.field)
+    }
+
+    /// Gets the field above which the chain is fully
+    /// polarized.
+    pub fn get_saturation_field(&self) -> Option<f32> {
+        self.steps.last().map(|step| step.field)
+    }
+}
+
+/// Finds the magnetization curve of a chain, by building
+/// a [`crate::model::Model`] for every sector from the
+/// smallest nonnegative S^z to S*length. The models are
+/// built by `model_builder`, whose other symmetries are
+/// kept, and the lowest energy of all their sectors is
+/// used.
+pub fn get_magnetization_curve<T: Implemented>(
+    hamiltonian: &T,
+    model_builder: ModelBuilder,
+    iterations: u32,
+) -> Result<MagnetizationCurve, Error> {
+    let base = model_builder.base;
+    let length = model_builder.length;
+    let twice_max_s_z = (base as i32 - 1) * length as i32;
+    let mut energies = Vec::new();
+
+    for twice_s_z in (twice_max_s_z % 2..=twice_max_s_z).step_by(2) {
+        let model = model_builder
+            .total_s_z(Some(SzSector::from_twice(twice_s_z)))
+            .build()?;
+        let energy = get_sector_energies(hamiltonian, &model, iterations)?
+            .iter()
+            .map(|sector_energy| sector_energy.energy)
+            .fold(f32::INFINITY, f32::min);
+        energies.push((twice_s_z as f32 / 2.0, energy));
+    }
+
+    let hull = get_lower_convex_hull(&energies);
+    let steps = hull
+        .windows(2)
+        .map(|window| MagnetizationStep {
+            field: (window[1].1 - window[0].1) / (window[1].0 - window[0].0),
+            magnetization: window[1].0 / length as f32,
+        })
+        .collect();
+
+    Ok(MagnetizationCurve {
+        length,
+        initial_magnetization: hull[0].0 / length as f32,
+        energies,
+        steps,
+    })
+}
+
+/// Gets the lower convex hull of the points (S^z, E(S^z)),
+/// which are sorted by S^z.
+fn get_lower_convex_hull(energies: &[(f32, f32)]) -> Vec<(f32, f32)> {
+    let mut hull: Vec<(f32, f32)> = Vec::with_capacity(energies.len());
+
+    for point in energies.iter().copied() {
+        while hull.len() >= 2 {
+            let (s_z_0, energy_0) = hull[hull.len() - 2];
+            let (s_z_1, energy_1) = hull[hull.len() - 1];
+
+            if (energy_1 - energy_0) * (point.0 - s_z_0) >= (point.1 - energy_0) * (s_z_1 - s_z_0) {
+                hull.pop();
+            } else {
+                break;
+            }
+        }
+
+        hull.push(point);
+    }
+
+    hull
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hamiltonians::{Hamiltonian, AFH};
+    use crate::model::Model;
+    use crate::sector::Sector;
+    use crate::symmetries::Reflection;
+
+    /// Builds a periodic chain with all the symmetries.
+    fn get_model_builder(base: u8, length: u8) -> ModelBuilder {
+        ModelBuilder::new(base, length)
+            .time_reversal(true)
+            .reflection(Some(Reflection::Bond))
+            .translation(true)
+    }
+
+    #[test]
+    fn heisenberg_chains_saturate_at_4js() {
+        let hamiltonian = Hamiltonian::<AFH>::new(1.0);
+
+        for (base, length) in [(2, 8), (3, 6)] {
+            let curve = get_magnetization_curve(&hamiltonian, get_model_builder(base, length), 1000).unwrap();
+            let spin = (base - 1) as f32 / 2.0;
+
+            assert!((curve.get_saturation_field().unwrap() - 4.0 * spin).abs() < 1e-3);
+            assert!((curve.get_magnetization(4.0 * spin + 1e-2) - spin).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn critical_field_is_the_gap_to_s_z_1() {
+        let hamiltonian = Hamiltonian::<AFH>::new(1.0);
+        let curve = get_magnetization_curve(&hamiltonian, get_model_builder(3, 6), 1000).unwrap();
+        let get_energy = |s_z| {
+            let model = Model::new(3, 6, SzSector::new(s_z)).unwrap();
+
+            Sector::get_all(&model)
+                .unwrap()
+                .iter()
+                .map(|sector| sector.find_eigenstate(&hamiltonian, 1000, &[]).unwrap().1)
+                .fold(f32::INFINITY, f32::min)
+        };
+        let gap = get_energy(1) - get_energy(0);
+
+        assert!((curve.get_critical_field().unwrap() - gap).abs() < 1e-3);
+        assert_eq!(curve.get_magnetization(0.9 * gap), 0.0);
+    }
+
+    #[test]
+    fn staircase_is_monotone() {
+        let hamiltonian = Hamiltonian::<AFH>::new(1.0);
+        let curve = get_magnetization_curve(&hamiltonian, get_model_builder(3, 6), 1000).unwrap();
+
+        for window in curve.steps.windows(2) {
+            assert!(window[0].field < window[1].field);
+            assert!(window[0].magnetization < window[1].magnetization);
+        }
+
+        let magnetizations: Vec<f32> = (0..=50)
+            .map(|index| curve.get_magnetization(index as f32 * 0.1))
+            .collect();
+
+        assert!(magnetizations.windows(2).all(|window| window[0] <= window[1]));
+    }
+
+    #[test]
+    fn hull_drops_points_above_it() {
+        let energies = [(0.0, 0.0), (1.0, 2.0), (2.0, 1.0), (3.0, 3.0)];
+
+        assert_eq!(get_lower_convex_hull(&energies), vec![(0.0, 0.0), (2.0, 1.0), (3.0, 3.0)]);
+        assert_eq!(get_lower_convex_hull(&energies[..1]), vec![(0.0, 0.0)]);
+    }
+}
\ No newline at end of file
@@ -1,9 +1,34 @@
 //! This module contains the [`Model`] struct,
-//! which holds the symmetrized basis states 
-//! and holds the power iteration algorithm.
-use crate::basis::BasisStates;
-use crate::states::State;
-use crate::hamiltonians::{Hamiltonian, Implemented};
+//! which holds the symmetrized basis states, 
+//! and the [`ModelBuilder`] struct, which selects 
+//! the symmetries of a [`Model`].
+use crate::basis::{check_chain, BasisStates, SzSector};
+use crate::error::Error;
+use crate::symmetries::{get_representer, Reflection, Symmetries, SymmetryFactors};
+
+/// The boundary conditions of the chain.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Boundary {
+    /// Site length-1 is coupled to site 0.
+    Periodic,
+    /// The chain has two ends.
+    Open,
+}
+
+impl Boundary {
+    /// Gets the number of bonds of the chain, which must
+    /// have at least two sites.
+    pub fn get_bonds(&self, length: u8) -> Result<usize, Error> {
+        if length < 2 {
+            return Err(Error::InvalidLength(length));
+        }
+
+        Ok(match self {
+            Boundary::Periodic => length as usize,
+            Boundary::Open => length as usize - 1,
+        })
+    }
+}
 
 /// Represents everything about the system except 
 /// the Hamiltonian.
@@ -11,113 +36,233 @@ pub struct Model {
     pub base: u8,
     pub spin: f32,
     pub length: u8,
-    pub total_s_z: u8,
+    /// The sector of total quantum spin in the 
+    /// z-direction, or `None` if S^z is not conserved.
+    pub total_s_z: Option<SzSector>,
+    pub symmetries: Symmetries,
+    pub boundary: Boundary,
+    /// Number of bonds of the chain, given by
+    /// [`Boundary::get_bonds`].
+    pub bonds: usize,
     pub base_powers: Vec<usize>,
     pub flippers: Vec<isize>,
     pub basis_states: BasisStates,
     pub m_coefficients: Vec<f32>,
     pub p_coefficients: Vec<f32>,
+    /// Number of threads used to apply Hamiltonians and 
+    /// to compute inner products. For a fixed number of 
+    /// threads the results are reproducible.
+    pub threads: usize,
+    /// Seed of the random starting states of the power 
+    /// iteration.
+    pub seed: u64,
 }
 
 impl Model {
-    /// Creates a new [`Model`].
+    /// Creates a new [`Model`] of a periodic chain using 
+    /// all the symmetries: S^z conservation, time reversal, 
+    /// bond-centered reflection and translation.
     pub fn new(
         base: u8,
         length: u8,
-        total_s_z: u8,
-    ) -> Self {
-        let spin = (base - 1) as f32 / 2.0;
-        let base_powers = get_base_powers(base, length);
-        let flippers = get_flippers(&base_powers);
-        let basis_states = BasisStates::new(
-            base,
-            spin,
-            length,
-            total_s_z,
-            &base_powers,
+        total_s_z: SzSector,
+    ) -> Result<Self, Error> {
+        ModelBuilder::new(base, length)
+            .total_s_z(Some(total_s_z))
+            .time_reversal(true)
+            .reflection(Some(Reflection::Bond))
+            .translation(true)
+            .build()
+    }
+
+    /// Creates a new [`Model`] of a periodic chain without 
+    /// symmetries, whose basis states are those with a 
+    /// given total quantum spin in the z-direction, or 
+    /// all basis states if `total_s_z` is `None`.
+    pub fn new_plain(
+        base: u8,
+        length: u8,
+        total_s_z: Option<SzSector>,
+    ) -> Result<Self, Error> {
+        ModelBuilder::new(base, length)
+            .total_s_z(total_s_z)
+            .build()
+    }
+
+    /// Checks that the symmetry factors belong to the 
+    /// basis states and that the sector is not empty.
+    pub fn check_symmetry_factors(&self, symmetry_factors: &SymmetryFactors) -> Result<(), Error> {
+        if symmetry_factors.factors.len() != self.basis_states.length {
+            return Err(Error::DimensionMismatch {
+                expected: self.basis_states.length,
+                found: symmetry_factors.factors.len(),
+            });
+        }
+
+        if symmetry_factors.factors.iter().all(|symmetry_factor| *symmetry_factor == 0.0) {
+            return Err(Error::EmptySector);
+        }
+
+        Ok(())
+    }
+
+    /// Finds the index of the representer of a basis 
+    /// state and the symmetry sign of a symmetry mapping
+    /// the basis state to it.
+    pub fn find_representer(&self, value: usize) -> Option<(usize, [bool; 3])> {
+        if self.basis_states.group_order == 1 {
+            return self.basis_states
+                .get_index(value)
+                .map(|basis_state_index| (basis_state_index, [false, false, false]));
+        }
+
+        let (representer_value, symmetry_sign) = get_representer(
+            value,
+            self.base,
+            self.length,
+            &self.symmetries,
         );
-        let m_coefficients = get_m_coefficients(base, spin);
-        let p_coefficients = get_p_coefficients(base, spin);
 
+        self.basis_states
+            .get_index(representer_value)
+            .map(|basis_state_index| (basis_state_index, symmetry_sign))
+    }
+}
+
+/// Builds a [`Model`] with independently selected 
+/// symmetries. By default no symmetries are used, 
+/// the boundaries are periodic, one thread is used 
+/// and the seed is 0.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct ModelBuilder {
+    pub base: u8,
+    pub length: u8,
+    pub total_s_z: Option<SzSector>,
+    pub symmetries: Symmetries,
+    pub boundary: Boundary,
+    pub threads: usize,
+    pub seed: u64,
+}
+
+impl ModelBuilder {
+    /// Creates a [`ModelBuilder`].
+    pub fn new(base: u8, length: u8) -> Self {
         Self {
             base,
-            spin,
             length,
-            total_s_z,
-            base_powers,
-            flippers,
-            basis_states,
-            m_coefficients,
-            p_coefficients,
+            total_s_z: None,
+            symmetries: Symmetries::none(),
+            boundary: Boundary::Periodic,
+            threads: 1,
+            seed: 0,
         }
     }
 
-    /// Executes the power iteration.
-    fn power_iterate<T>(
-        &self,
-        hamiltonian: &Hamiltonian<T>,
-        iterations: u32,
-        symmetry_factors: &Vec<f32>,
-        lower_eigenpairs: &Vec<(State, f32)>,
-    ) -> State where Hamiltonian<T>: Implemented {
-        let mut state_0 = State::from_random(self.basis_states.length);
-        let mut state_1 = State::from_zeros(self.basis_states.length);
-        let max_eigenenergy = hamiltonian.get_max_eigenenergy(&self);
-
-        for _ in 0..iterations/2 {
-            hamiltonian.apply(&state_0, &mut state_1, &self, symmetry_factors);
-            state_1 -= &state_0 * max_eigenenergy;
-
-            for (lower_eigenstate, lower_eigenenergy) in lower_eigenpairs.iter() {
-                state_1 -= lower_eigenstate * (lower_eigenenergy * lower_eigenstate.dot(&state_0, self.length, &self.basis_states, &symmetry_factors));
-            }
-
-            state_0.clear();
-
-            hamiltonian.apply(&state_1, &mut state_0, &self, symmetry_factors);
-            state_0 -= &state_1 * max_eigenenergy;
-
-            for (lower_eigenstate, lower_eigenenergy) in lower_eigenpairs.iter() {
-                state_0 -= lower_eigenstate * (lower_eigenenergy * lower_eigenstate.dot(&state_1, self.length, &self.basis_states, &symmetry_factors));
-            }
-
-            state_1.clear();
-            state_0.normalize(self.length, &self.basis_states, symmetry_factors);
-        }
-        
-        state_0
+    /// Restricts to a sector of total quantum spin in the 
+    /// z-direction, or uses all basis states if `None`.
+    pub fn total_s_z(mut self, total_s_z: Option<SzSector>) -> Self {
+        self.total_s_z = total_s_z;
+        self.symmetries.s_z_conservation = total_s_z.is_some();
+        self
+    }
+
+    /// Selects whether time reversal (spin flip) is used. 
+    /// It is only used if S^z is not conserved or is 0, 
+    /// since it maps S^z to -S^z.
+    pub fn time_reversal(mut self, time_reversal: bool) -> Self {
+        self.symmetries.time_reversal = time_reversal;
+        self
+    }
+
+    /// Selects the reflection used, if any.
+    pub fn reflection(mut self, reflection: Option<Reflection>) -> Self {
+        self.symmetries.reflection = reflection;
+        self
     }
 
-    /// Finds the eigenvector of the Hamtilonian with 
-    /// the smallest eigenvalue.
-    pub fn find_eigenstate<T>(
-        &mut self,
-        hamiltonian: &Hamiltonian<T>,
-        iterations: u32,
-        symmetry_factors: &Vec<f32>,
-        lower_eigenpairs: &Vec<(State, f32)>,
-    ) -> (State, f32) where Hamiltonian<T>: Implemented {
-        let eigenstate = self.power_iterate(hamiltonian, iterations, symmetry_factors, lower_eigenpairs);
-        let mut eigenstate_times_eigenenergy = State::from_zeros(self.basis_states.length);
-        hamiltonian.apply(&eigenstate, &mut eigenstate_times_eigenenergy, &self, symmetry_factors);
-        let eigenenergy = eigenstate.dot(&eigenstate_times_eigenenergy, self.length, &self.basis_states, symmetry_factors);
-
-        (eigenstate, eigenenergy)
+    /// Selects whether translation is used.
+    pub fn translation(mut self, translation: bool) -> Self {
+        self.symmetries.translation = translation;
+        self
+    }
+
+    /// Selects the boundary conditions.
+    pub fn boundary(mut self, boundary: Boundary) -> Self {
+        self.boundary = boundary;
+        self
+    }
+
+    /// Selects the number of threads.
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.threads = threads;
+        self
+    }
+
+    /// Selects the seed of the random starting states of 
+    /// the power iteration.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Builds the [`Model`].
+    pub fn build(self) -> Result<Model, Error> {
+        check_chain(self.base, self.length, self.total_s_z)?;
+
+        if self.boundary == Boundary::Open && (
+            self.symmetries.translation 
+                || self.symmetries.reflection == Some(Reflection::Site)
+        ) {
+            return Err(Error::OpenBoundarySymmetry);
+        }
+
+        let symmetries = self.symmetries.restrict(self.total_s_z);
+        let spin = (self.base - 1) as f32 / 2.0;
+        let bonds = self.boundary.get_bonds(self.length)?;
+        let base_powers = get_base_powers(self.base, self.length)?;
+        let flippers = get_flippers(&base_powers);
+        let basis_states = BasisStates::new(
+            self.base,
+            self.length,
+            self.total_s_z,
+            &symmetries,
+        )?;
+        let m_coefficients = get_m_coefficients(self.base, spin);
+        let p_coefficients = get_p_coefficients(self.base, spin);
+
+        Ok(Model {
+            base: self.base,
+            spin,
+            length: self.length,
+            total_s_z: self.total_s_z,
+            symmetries,
+            boundary: self.boundary,
+            bonds,
+            base_powers,
+            flippers,
+            basis_states,
+            m_coefficients,
+            p_coefficients,
+            threads: self.threads,
+            seed: self.seed,
+        })
     }
 }
 
 /// Calculates powers of the base.
-pub fn get_base_powers(base: u8, length: u8) -> Vec<usize> {
-    (0..=length)
+pub fn get_base_powers(base: u8, length: u8) -> Result<Vec<usize>, Error> {
+    check_chain(base, length, None)?;
+
+    Ok((0..=length)
         .map(
             |power| (base as usize).pow((power % length) as u32)
         )
-        .collect()
+        .collect())
 }
 
 /// Calculates the integers, which mimics the 
 /// action of S^+_n S^-_{n+1}.
-pub fn get_flippers(base_powers: &Vec<usize>) -> Vec<isize> {
+pub fn get_flippers(base_powers: &[usize]) -> Vec<isize> {
     (0..base_powers.len() - 1)
         .map(
             |index| base_powers[index + 1] as isize - base_powers[index] as isize
@@ -143,4 +288,18 @@ fn get_p_coefficients(base: u8, spin: f32) -> Vec<f32> {
                 .sqrt()
         )
         .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_chains_are_rejected() {
+        assert_eq!(get_base_powers(3, 0), Err(Error::InvalidLength(0)));
+        assert_eq!(Boundary::Open.get_bonds(0), Err(Error::InvalidLength(0)));
+        assert_eq!(Boundary::Periodic.get_bonds(0), Err(Error::InvalidLength(0)));
+        assert_eq!(get_base_powers(3, 3), Ok(vec![1, 3, 9, 1]));
+        assert_eq!(Boundary::Open.get_bonds(4), Ok(3));
+    }
 }
\ No newline at end of file
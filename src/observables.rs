@@ -0,0 +1,262 @@
+//! This module contains the observables used to detect
+//! the Haldane phase: the twist operator of Resta and
+//! the partial reflection and partial translation
+//! overlaps of Pollmann and Turner. They are calculated
+//! from a [`State`] of a [`Sector`] by visiting the
+//! equivalence classes of the representers, without
+//! building the full vector.
+use crate::error::Error;
+use crate::sector::Sector;
+use crate::states::State;
+use crate::symmetries::get_eq_class;
+use num_complex::Complex32;
+use std::f32::consts::PI;
+
+/// Gets the expectation value of the twist operator
+/// exp(i 2π/L Σ_j j S^z_j), where the sites are numbered
+/// from 0. It is close to -1 in the Haldane phase and
+/// close to 1 in the trivial phase.
+pub fn get_twist_expectation_value(
+    state: &State,
+    sector: &Sector,
+) -> Result<Complex32, Error> {
+    let model = sector.model;
+    let mut twist_expectation_value = Complex32::new(0.0, 0.0);
+    let mut norm_squared = 0.0;
+
+    for_each_component(state, sector, |value, coefficient| {
+        let mut remaining_value = value;
+        let mut twisted_s_z = 0.0;
+
+        for site in 0..model.length {
+            let sigma = (remaining_value % model.base as usize) as f32 - model.spin;
+            twisted_s_z += site as f32 * sigma;
+            remaining_value /= model.base as usize;
+        }
+
+        let angle = 2.0 * PI * twisted_s_z / model.length as f32;
+        twist_expectation_value += Complex32::from_polar(coefficient * coefficient, angle);
+        norm_squared += coefficient * coefficient;
+    })?;
+
+    if norm_squared == 0.0 || !norm_squared.is_finite() {
+        return Err(Error::VanishingNorm);
+    }
+
+    Ok(twist_expectation_value / norm_squared)
+}
+
+/// Gets the expectation value of the reflection of the
+/// sites `start..start+block_length`, which maps site
+/// start+n to site start+block_length-1-n. In the Haldane
+/// phase it is negative for long blocks.
+pub fn get_partial_reflection_overlap(
+    state: &State,
+    sector: &Sector,
+    start: u8,
+    block_length: u8,
+) -> Result<f32, Error> {
+    get_partial_overlap(state, sector, start, block_length, |index| block_length as usize - 1 - index)
+}
+
+/// Gets the expectation value of the translation of the
+/// sites `start..start+block_length` by one site, which
+/// maps site start+n to site start+(n+1)%block_length.
+pub fn get_partial_translation_overlap(
+    state: &State,
+    sector: &Sector,
+    start: u8,
+    block_length: u8,
+) -> Result<f32, Error> {
+    get_partial_overlap(state, sector, start, block_length, |index| (index + 1) % block_length as usize)
+}
+
+/// Gets the expectation value of a permutation of the
+/// sites `start..start+block_length`, which maps site
+/// start+n to site start+`permutation`(n).
+fn get_partial_overlap<F>(
+    state: &State,
+    sector: &Sector,
+    start: u8,
+    block_length: u8,
+    permutation: F,
+) -> Result<f32, Error> where F: Fn(usize) -> usize {
+    let model = sector.model;
+
+    if block_length == 0 || start as usize + block_length as usize > model.length as usize {
+        return Err(Error::InvalidBlock {
+            start,
+            block_length,
+            length: model.length,
+        });
+    }
+
+    let base = model.base as usize;
+    let start_power = base.pow(start as u32);
+    let block_power = base.pow(block_length as u32);
+    let mut overlap = 0.0;
+    let mut norm_squared = 0.0;
+
+    for_each_component(state, sector, |value, coefficient| {
+        let block_value = (value / start_power) % block_power;
+        let mut remaining_block_value = block_value;
+        let mut permuted_block_value = 0;
+
+        for index in 0..block_length as usize {
+            let digit = remaining_block_value % base;
+            permuted_block_value += digit * base.pow(permutation(index) as u32);
+            remaining_block_value /= base;
+        }
+
+        let permuted_value = value - block_value * start_power + permuted_block_value * start_power;
+        overlap += coefficient * get_component(state, sector, permuted_value);
+        norm_squared += coefficient * coefficient;
+    })?;
+
+    if norm_squared == 0.0 || !norm_squared.is_finite() {
+        return Err(Error::VanishingNorm);
+    }
+
+    Ok(overlap / norm_squared)
+}
+
+/// Visits the nonzero coefficients of the full vector
+/// of a [`State`] of a [`Sector`].
+fn for_each_component<F>(
+    state: &State,
+    sector: &Sector,
+    mut function: F,
+) -> Result<(), Error> where F: FnMut(usize, f32) {
+    state.check_sector(sector)?;
+
+    let model = sector.model;
+    let symmetry_factors = &sector.symmetry_factors;
+
+    for (basis_state_index, representer_value) in model.basis_states.representers.iter().enumerate() {
+        let coefficient = state.coefficients[basis_state_index];
+
+        if symmetry_factors.factors[basis_state_index] == 0.0 || coefficient == 0.0 {
+            continue;
+        }
+
+        let mut eq_class = get_eq_class(*representer_value, model.base, model.length, &model.symmetries);
+        eq_class.sort_by_key(|(value, _)| *value);
+        eq_class.dedup_by_key(|(value, _)| *value);
+
+        for (value, symmetry_sign) in eq_class {
+            function(value, coefficient * symmetry_factors.get_sign(symmetry_sign));
+        }
+    }
+
+    Ok(())
+}
+
+/// Gets a coefficient of the full vector of a [`State`]
+/// of a [`Sector`].
+fn get_component(state: &State, sector: &Sector, value: usize) -> f32 {
+    match sector.model.find_representer(value) {
+        Some((basis_state_index, symmetry_sign)) if sector.symmetry_factors.factors[basis_state_index] != 0.0 => {
+            state.coefficients[basis_state_index] * sector.symmetry_factors.get_sign(symmetry_sign)
+        },
+        _ => 0.0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::basis::SzSector;
+    use crate::hamiltonians::{Hamiltonian, AFH, AKLT};
+    use crate::model::Model;
+    use crate::symmetries::{Momentum, Parity};
+
+    /// Gets the expectation value of a permutation of the
+    /// sites, given by the site each site is moved to, in
+    /// the full vector of a [`State`].
+    fn get_brute_force_overlap(full_state: &[f32], base: u8, permutation: &[usize]) -> f32 {
+        let base = base as usize;
+        let mut overlap = 0.0;
+
+        for (value, coefficient) in full_state.iter().enumerate() {
+            let mut remaining_value = value;
+            let mut permuted_value = 0;
+
+            for permuted_site in permutation {
+                permuted_value += (remaining_value % base) * base.pow(*permuted_site as u32);
+                remaining_value /= base;
+            }
+
+            overlap += coefficient * full_state[permuted_value];
+        }
+
+        overlap / full_state.iter().map(|coefficient| coefficient * coefficient).sum::<f32>()
+    }
+
+    /// Gets the expectation value of the twist operator in
+    /// the full vector of a [`State`].
+    fn get_brute_force_twist(full_state: &[f32], base: u8, length: u8) -> Complex32 {
+        let spin = (base as f32 - 1.0) / 2.0;
+        let mut twist = Complex32::new(0.0, 0.0);
+
+        for (value, coefficient) in full_state.iter().enumerate() {
+            let twisted_s_z: f32 = (0..length as u32)
+                .map(|site| site as f32 * ((value / (base as usize).pow(site)) % base as usize) as f32 - site as f32 * spin)
+                .sum();
+            twist += Complex32::from_polar(coefficient * coefficient, 2.0 * PI * twisted_s_z / length as f32);
+        }
+
+        twist / full_state.iter().map(|coefficient| coefficient * coefficient).sum::<f32>()
+    }
+
+    #[test]
+    fn observables_agree_with_the_full_vector() {
+        let model = Model::new(3, 6, SzSector::new(0)).unwrap();
+
+        for sector in Sector::get_all(&model).unwrap() {
+            let state = sector.from_random(3);
+            let full_state = state.get_full_state(&sector).unwrap();
+            let twist = get_twist_expectation_value(&state, &sector).unwrap();
+
+            assert!((twist - get_brute_force_twist(&full_state, 3, 6)).norm() < 1e-4);
+
+            for (start, block_length) in [(0, 1), (0, 3), (1, 4), (2, 4), (0, 6)] {
+                let block = start as usize..(start + block_length) as usize;
+                let reflection: Vec<usize> = (0..6)
+                    .map(|site| if block.contains(&site) {block.start + block.end - 1 - site} else {site})
+                    .collect();
+                let translation: Vec<usize> = (0..6)
+                    .map(|site| if block.contains(&site) {block.start + (site - block.start + 1) % block.len()} else {site})
+                    .collect();
+                let reflection_overlap = get_partial_reflection_overlap(&state, &sector, start, block_length).unwrap();
+                let translation_overlap = get_partial_translation_overlap(&state, &sector, start, block_length).unwrap();
+
+                assert!((reflection_overlap - get_brute_force_overlap(&full_state, 3, &reflection)).abs() < 1e-4);
+                assert!((translation_overlap - get_brute_force_overlap(&full_state, 3, &translation)).abs() < 1e-4);
+            }
+        }
+    }
+
+    #[test]
+    fn haldane_and_trivial_phases_have_opposite_signs() {
+        let model = Model::new(3, 8, SzSector::new(0)).unwrap();
+        let sector = Sector::new(&model, Parity::Even, Parity::Even, Momentum::Zero).unwrap();
+        let haldane_ground_states = [
+            sector.find_eigenstate(&Hamiltonian::<AKLT>::new(1.0), 1000, &[]).unwrap().0,
+            sector.find_eigenstate(&Hamiltonian::<AFH>::new(1.0), 1000, &[]).unwrap().0,
+        ];
+        let (trivial_ground_state, _) = sector.find_eigenstate(&Hamiltonian::<AKLT>::new(0.0), 1000, &[]).unwrap();
+
+        for ground_state in haldane_ground_states.iter() {
+            let twist = get_twist_expectation_value(ground_state, &sector).unwrap();
+            let reflection_overlap = get_partial_reflection_overlap(ground_state, &sector, 0, 4).unwrap();
+            assert!(twist.re < 0.0 && twist.im.abs() < 1e-4);
+            assert!(reflection_overlap < 0.0);
+        }
+
+        let twist = get_twist_expectation_value(&trivial_ground_state, &sector).unwrap();
+        let reflection_overlap = get_partial_reflection_overlap(&trivial_ground_state, &sector, 0, 4).unwrap();
+
+        assert!((twist.re - 1.0).abs() < 1e-4 && twist.im.abs() < 1e-4);
+        assert!((reflection_overlap - 1.0).abs() < 1e-4);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,221 @@
+//! This module contains the symmetry operators, which
+//! act on vectors in the full basis by permuting the
+//! basis states up to signs, and on [`State`]s of a
+//! [`Sector`] by their eigenvalues.
+use crate::basis::check_chain;
+use crate::error::Error;
+use crate::sector::Sector;
+use crate::states::State;
+use crate::symmetries::{
+    get_pi_rotation_phase,
+    reflect,
+    rotate_x,
+    rotate_z,
+    time_reverse,
+    translate,
+    Momentum,
+    Parity,
+    Reflection,
+};
+
+/// A symmetry operator.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Operator {
+    /// Time reversal (spin flip).
+    TimeReversal,
+    /// Reflection with a given center.
+    Reflection(Reflection),
+    /// Translation by one site, which moves site n to
+    /// site n+1 (modulo length).
+    Translation,
+    /// The π-rotation exp(iπS^x_tot), which is time
+    /// reversal times the phase e^{iπSL}. It is only
+    /// real if S*length is an integer.
+    PiRotationX,
+    /// The π-rotation exp(iπS^z_tot). It is only real
+    /// if S*length is an integer.
+    PiRotationZ,
+}
+
+/// Applies a symmetry operator to a vector in the full
+/// basis of a chain, which must have base^length
+/// coefficients.
+pub fn apply_operator(
+    full_state: &[f32],
+    base: u8,
+    length: u8,
+    operator: Operator,
+) -> Result<Vec<f32>, Error> {
+    check_chain(base, length, None)?;
+
+    let max_value = (base as usize).pow(length as u32);
+
+    if full_state.len() != max_value {
+        return Err(Error::FullStateLengthMismatch {
+            expected: max_value,
+            found: full_state.len(),
+        });
+    }
+
+    let mut transformed_state = vec![0.0; full_state.len()];
+
+    for (value, coefficient) in full_state.iter().enumerate() {
+        let (transformed_value, phase) = match operator {
+            Operator::TimeReversal => (time_reverse(value, base, length), 1.0),
+            Operator::Reflection(reflection) => (reflect(value, base, length, reflection)?, 1.0),
+            Operator::Translation => (translate(value, base, length)?, 1.0),
+            Operator::PiRotationX => rotate_x(value, base, length)
+                .ok_or(Error::NonRealOperator)?,
+            Operator::PiRotationZ => (value, rotate_z(value, base, length)
+                .ok_or(Error::NonRealOperator)?),
+        };
+
+        transformed_state[transformed_value] = *coefficient * phase;
+    }
+
+    Ok(transformed_state)
+}
+
+/// Gets the expectation value of a symmetry operator
+/// in a vector in the full basis, which is its
+/// eigenvalue if the vector is an eigenvector.
+pub fn get_expectation_value(
+    full_state: &[f32],
+    base: u8,
+    length: u8,
+    operator: Operator,
+) -> Result<f32, Error> {
+    let transformed_state = apply_operator(full_state, base, length, operator)?;
+    let norm_squared: f32 = full_state.iter().map(|coefficient| coefficient * coefficient).sum();
+
+    if norm_squared == 0.0 || !norm_squared.is_finite() {
+        return Err(Error::VanishingNorm);
+    }
+
+    let overlap: f32 = full_state
+        .iter()
+        .zip(transformed_state.iter())
+        .map(|(coefficient, transformed_coefficient)| coefficient * transformed_coefficient)
+        .sum();
+
+    Ok(overlap / norm_squared)
+}
+
+/// Gets the eigenvalue of a symmetry operator of a
+/// [`State`] of a [`Sector`]. If the operator is one of
+/// the symmetries of the [`Sector`], the eigenvalue is
+/// the one of the [`Sector`]. Otherwise, it is the
+/// expectation value in the full basis.
+pub fn get_eigenvalue(
+    state: &State,
+    sector: &Sector,
+    operator: Operator,
+) -> Result<f32, Error> {
+    state.check_sector(sector)?;
+
+    let model = sector.model;
+    let symmetries = &model.symmetries;
+    let sign = |odd: bool| if odd {-1.0} else {1.0};
+
+    match operator {
+        Operator::TimeReversal if symmetries.time_reversal => {
+            Ok(sign(sector.time_reversal == Parity::Odd))
+        },
+        Operator::Reflection(reflection) if symmetries.reflection == Some(reflection) => {
+            Ok(sign(sector.reflection == Parity::Odd))
+        },
+        Operator::Translation if symmetries.translation => {
+            Ok(sign(sector.translation == Momentum::Pi))
+        },
+        Operator::PiRotationX if symmetries.time_reversal => {
+            let phase = get_pi_rotation_phase(model.base, model.length)
+                .ok_or(Error::NonRealOperator)?;
+
+            Ok(phase * sign(sector.time_reversal == Parity::Odd))
+        },
+        Operator::PiRotationZ if model.total_s_z.is_some() => {
+            // All the basis states have the same total quantum
+            // spin in the z-direction.
+            let state_value = model.basis_states.representers[0];
+
+            rotate_z(state_value, model.base, model.length).ok_or(Error::NonRealOperator)
+        },
+        _ => {
+            let full_state = state.get_full_state(sector)?;
+
+            get_expectation_value(&full_state, model.base, model.length, operator)
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::basis::SzSector;
+    use crate::hamiltonians::{Hamiltonian, AFH};
+    use crate::symmetries::Reflection;
+    use crate::testing::get_model;
+
+    #[test]
+    fn full_states_of_the_wrong_length_are_rejected() {
+        for found in [8, 10] {
+            assert_eq!(
+                apply_operator(&vec![1.0; found], 3, 2, Operator::Translation),
+                Err(Error::FullStateLengthMismatch {expected: 9, found}),
+            );
+        }
+    }
+
+    #[test]
+    fn translations_around_the_chain_give_the_identity() {
+        let full_state: Vec<f32> = (0..81).map(|value| value as f32).collect();
+        let mut transformed_state = full_state.clone();
+
+        for _ in 0..4 {
+            transformed_state = apply_operator(&transformed_state, 3, 4, Operator::Translation).unwrap();
+        }
+
+        assert_eq!(transformed_state, full_state);
+    }
+
+    #[test]
+    fn eigenstates_carry_the_eigenvalues_of_their_sector() {
+        let hamiltonian = Hamiltonian::<AFH>::new(1.0);
+
+        // Time reversal is only a symmetry for S^z = 0, and
+        // the π-rotation about z has the eigenvalue -1 for
+        // S^z = 1.
+        for (s_z, reflection) in [(0, Reflection::Bond), (0, Reflection::Site), (1, Reflection::Bond)] {
+            let model = get_model(3, 6, SzSector::new(s_z), true, Some(reflection), true);
+            let operators = if s_z == 0 {
+                vec![
+                    Operator::TimeReversal,
+                    Operator::Reflection(reflection),
+                    Operator::Translation,
+                    Operator::PiRotationX,
+                    Operator::PiRotationZ,
+                ]
+            } else {
+                vec![Operator::Reflection(reflection), Operator::Translation, Operator::PiRotationZ]
+            };
+
+            for sector in Sector::get_all(&model).unwrap() {
+                let (eigenstate, _) = sector.find_eigenstate(&hamiltonian, 300, &[]).unwrap();
+                let full_state = eigenstate.get_full_state(&sector).unwrap();
+                let scale = full_state.iter().fold(0.0f32, |scale, coefficient| scale.max(coefficient.abs()));
+
+                for operator in operators.iter().copied() {
+                    let eigenvalue = get_eigenvalue(&eigenstate, &sector, operator).unwrap();
+                    let transformed_state = apply_operator(&full_state, 3, 6, operator).unwrap();
+
+                    for (transformed_coefficient, coefficient) in transformed_state.iter().zip(full_state.iter()) {
+                        assert!(
+                            (transformed_coefficient - eigenvalue * coefficient).abs() < 1e-5 * scale,
+                            "{:?} in {:?} {:?} {:?}", operator, sector.time_reversal, sector.reflection, sector.translation,
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,70 @@
+//! This module contains the helpers used to split
+//! work over a fixed number of threads. The work is
+//! always split into the same contiguous chunks for a
+//! given number of threads, so the results are
+//! reproducible.
+use std::ops::Range;
+use std::thread;
+
+/// Splits `0..length` into contiguous chunks, one per thread.
+pub fn get_chunks(length: usize, threads: usize) -> Vec<Range<usize>> {
+    let threads = threads.max(1);
+    let chunk_length = length.div_ceil(threads).max(1);
+
+    (0..length)
+        .step_by(chunk_length)
+        .map(|start| start..(start + chunk_length).min(length))
+        .collect()
+}
+
+/// Applies a function to contiguous chunks of a slice
+/// in parallel. The function is given the offset of
+/// the chunk in the slice.
+pub fn for_each_chunk<F>(values: &mut [f32], threads: usize, function: F)
+where F: Fn(usize, &mut [f32]) + Sync {
+    if threads <= 1 {
+        function(0, values);
+        return;
+    }
+
+    let chunks = get_chunks(values.len(), threads);
+
+    thread::scope(|scope| {
+        let mut rest = values;
+
+        for chunk in chunks {
+            let (values, next_rest) = rest.split_at_mut(chunk.len());
+            rest = next_rest;
+            let function = &function;
+            scope.spawn(move || function(chunk.start, values));
+        }
+    });
+}
+
+/// Sums a function over contiguous chunks of `0..length`
+/// in parallel. The partial sums are added in the order
+/// of the chunks.
+pub fn sum_chunks<F>(length: usize, threads: usize, function: F) -> f32
+where F: Fn(Range<usize>) -> f32 + Sync {
+    if threads <= 1 {
+        return function(0..length);
+    }
+
+    let chunks = get_chunks(length, threads);
+    let partial_sums: Vec<f32> = thread::scope(|scope| {
+        let handles: Vec<_> = chunks
+            .into_iter()
+            .map(|chunk| {
+                let function = &function;
+                scope.spawn(move || function(chunk))
+            })
+            .collect();
+
+        handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .collect()
+    });
+
+    partial_sums.iter().sum()
+}
\ No newline at end of file
@@ -0,0 +1,367 @@
+//! This module contains the finite-size scaling of
+//! values calculated for a list of chain lengths, such
+//! as ground state energies per site and spectral gaps.
+//! They are extrapolated to infinite length by fits to
+//! a + b/L^p and a + b e^{-L/ξ}, and by the Shanks and
+//! Bulirsch-Stoer (BST) transformations. The values of
+//! neighbouring lengths are close, so the calculations
+//! are done in double precision.
+use crate::error::Error;
+
+/// A value extrapolated to infinite length.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Extrapolation {
+    pub value: f32,
+    /// An estimate of the error of the value.
+    pub uncertainty: f32,
+}
+
+/// A fit to a + b/L^p, where a is the extrapolated
+/// value, b is the amplitude and p is the exponent.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct PowerLawFit {
+    pub extrapolation: Extrapolation,
+    pub amplitude: f32,
+    /// An estimate of the error of the amplitude, found
+    /// in the same way as that of the value.
+    pub amplitude_uncertainty: f32,
+    pub exponent: f32,
+}
+
+/// A fit to a + b e^{-L/ξ}, where a is the extrapolated
+/// value, b is the amplitude and ξ is the correlation
+/// length.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct ExponentialFit {
+    pub extrapolation: Extrapolation,
+    pub amplitude: f32,
+    pub correlation_length: f32,
+}
+
+/// Calculates a value for every chain length, which
+/// gives the sequence used by the extrapolations.
+pub fn get_sequence<F>(
+    lengths: &[u8],
+    mut get_value: F,
+) -> Result<Vec<(u8, f32)>, Error> where F: FnMut(u8) -> Result<f32, Error> {
+    lengths
+        .iter()
+        .map(|length| Ok((*length, get_value(*length)?)))
+        .collect()
+}
+
+/// Fits a + b/L^p to the values by least squares for a
+/// given exponent p, for example p = 2 for the ground
+/// state energy per site of a critical chain. The
+/// uncertainty is the larger of the standard error of
+/// a and its change when the shortest chain is left
+/// out. At least three lengths are required.
+pub fn fit_power_law(
+    values: &[(u8, f32)],
+    exponent: f32,
+) -> Result<PowerLawFit, Error> {
+    let points = get_points(values, 3)?;
+    let get_line_points = |points: &[(f64, f64)]| -> Vec<(f64, f64)> {
+        points
+            .iter()
+            .map(|(length, value)| (length.powf(-exponent as f64), *value))
+            .collect()
+    };
+    let (value, amplitude, standard_error, amplitude_standard_error) = fit_line(&get_line_points(&points))?;
+    let (reduced_value, reduced_amplitude, _, _) = fit_line(&get_line_points(&points[1..]))?;
+
+    Ok(PowerLawFit {
+        extrapolation: Extrapolation {
+            value: value as f32,
+            uncertainty: standard_error.max((value - reduced_value).abs()) as f32,
+        },
+        amplitude: amplitude as f32,
+        amplitude_uncertainty: amplitude_standard_error.max((amplitude - reduced_amplitude).abs()) as f32,
+        exponent,
+    })
+}
+
+/// Fits a + b e^{-L/ξ} to the values by least squares,
+/// for example for the spectral gap of a gapped chain.
+/// The uncertainty is the larger of the standard error
+/// of a at the fitted ξ and its change when the
+/// shortest chain is left out. At least four lengths
+/// are required.
+pub fn fit_exponential(values: &[(u8, f32)]) -> Result<ExponentialFit, Error> {
+    let points = get_points(values, 4)?;
+    let (value, amplitude, correlation_length, standard_error) = fit_exponential_points(&points)?;
+    let (reduced_value, _, _, _) = fit_exponential_points(&points[1..])?;
+
+    Ok(ExponentialFit {
+        extrapolation: Extrapolation {
+            value: value as f32,
+            uncertainty: standard_error.max((value - reduced_value).abs()) as f32,
+        },
+        amplitude: amplitude as f32,
+        correlation_length: correlation_length as f32,
+    })
+}
+
+/// Extrapolates the values by the iterated Shanks
+/// transformation, which is exact for a + b e^{-L/ξ}
+/// if the lengths are equally spaced. The uncertainty
+/// is the difference between the two last values of
+/// the last transformation, or between the last value
+/// and the last value of the previous transformation
+/// if there is only one. At least three lengths are
+/// required.
+pub fn extrapolate_shanks(values: &[(u8, f32)]) -> Result<Extrapolation, Error> {
+    let points = get_points(values, 3)?;
+    let mut previous_sequence: Vec<f64> = points.iter().map(|(_, value)| *value).collect();
+    let mut sequence = get_shanks_transformation(&previous_sequence);
+
+    while sequence.len() >= 3 {
+        previous_sequence = std::mem::take(&mut sequence);
+        sequence = get_shanks_transformation(&previous_sequence);
+    }
+
+    let value = sequence[sequence.len() - 1];
+    let other_value = match sequence.len() {
+        1 => previous_sequence[previous_sequence.len() - 1],
+        _ => sequence[sequence.len() - 2],
+    };
+
+    Ok(Extrapolation {
+        value: value as f32,
+        uncertainty: (value - other_value).abs() as f32,
+    })
+}
+
+/// Extrapolates the values by the BST algorithm of
+/// Bulirsch and Stoer, which assumes that the leading
+/// finite-size corrections are powers of 1/L^ω. The
+/// uncertainty is the difference between the two values
+/// of the column before the last. At least three
+/// lengths are required.
+pub fn extrapolate_bst(
+    values: &[(u8, f32)],
+    omega: f32,
+) -> Result<Extrapolation, Error> {
+    let points = get_points(values, 3)?;
+    let omega = omega as f64;
+    let mut previous_column = vec![0.0; points.len()];
+    let mut column: Vec<f64> = points.iter().map(|(_, value)| *value).collect();
+
+    while column.len() > 1 {
+        let order = points.len() - column.len() + 1;
+        let next_column = (0..column.len() - 1)
+            .map(|index| {
+                let difference = column[index + 1] - column[index];
+                let previous_difference = column[index + 1] - previous_column[index + 1];
+                let ratio = (points[index + order].0 / points[index].0).powf(omega);
+                let denominator = ratio * (1.0 - difference / previous_difference) - 1.0;
+
+                if difference == 0.0 || previous_difference == 0.0 || denominator == 0.0 {
+                    column[index + 1]
+                } else {
+                    column[index + 1] + difference / denominator
+                }
+            })
+            .collect();
+        previous_column = std::mem::replace(&mut column, next_column);
+    }
+
+    Ok(Extrapolation {
+        value: column[0] as f32,
+        uncertainty: (previous_column[1] - previous_column[0]).abs() as f32,
+    })
+}
+
+/// Checks that there are enough values and sorts them
+/// by length.
+fn get_points(values: &[(u8, f32)], required: usize) -> Result<Vec<(f64, f64)>, Error> {
+    if values.len() < required {
+        return Err(Error::TooFewLengths {
+            required,
+            found: values.len(),
+        });
+    }
+
+    let mut points: Vec<(f64, f64)> = values
+        .iter()
+        .map(|(length, value)| (*length as f64, *value as f64))
+        .collect();
+    points.sort_by(|point_0, point_1| point_0.0.total_cmp(&point_1.0));
+
+    Ok(points)
+}
+
+/// Fits a + b x to points (x, y) by least squares and
+/// gets a, b and the standard errors of a and b.
+fn fit_line(points: &[(f64, f64)]) -> Result<(f64, f64, f64, f64), Error> {
+    let count = points.len() as f64;
+    let x_sum: f64 = points.iter().map(|(x, _)| x).sum();
+    let y_sum: f64 = points.iter().map(|(_, y)| y).sum();
+    let x_squared_sum: f64 = points.iter().map(|(x, _)| x * x).sum();
+    let xy_sum: f64 = points.iter().map(|(x, y)| x * y).sum();
+    let determinant = count * x_squared_sum - x_sum * x_sum;
+
+    if points.len() < 2 || determinant.abs() <= f64::EPSILON * count * x_squared_sum {
+        return Err(Error::SingularFit);
+    }
+
+    let slope = (count * xy_sum - x_sum * y_sum) / determinant;
+    let intercept = (y_sum - slope * x_sum) / count;
+    let variance = match points.len() {
+        2 => 0.0,
+        _ => points
+            .iter()
+            .map(|(x, y)| (y - intercept - slope * x).powi(2))
+            .sum::<f64>() / (count - 2.0),
+    };
+
+    Ok((
+        intercept,
+        slope,
+        (variance * x_squared_sum / determinant).sqrt(),
+        (variance * count / determinant).sqrt(),
+    ))
+}
+
+/// Fits a + b e^{-L/ξ} to points (L, y) by least squares
+/// and gets a, b, ξ and the standard error of a at the
+/// fitted ξ. For a given ξ the fit is linear, so the sum
+/// of squared residuals is minimized over ln ξ by a scan
+/// followed by a golden-section search.
+fn fit_exponential_points(points: &[(f64, f64)]) -> Result<(f64, f64, f64, f64), Error> {
+    // The exponentials are taken relative to the shortest
+    // length, so they do not underflow for small ξ.
+    let shortest_length = points[0].0;
+    let fit = |log_correlation_length: f64| {
+        let correlation_length = log_correlation_length.exp();
+        let line_points: Vec<(f64, f64)> = points
+            .iter()
+            .map(|(length, value)| (
+                (-(length - shortest_length) / correlation_length).exp(),
+                *value,
+            ))
+            .collect();
+        let (intercept, slope, standard_error, _) = fit_line(&line_points)?;
+        let residual: f64 = line_points
+            .iter()
+            .map(|(x, y)| (y - intercept - slope * x).powi(2))
+            .sum();
+
+        Ok((residual, intercept, slope, standard_error))
+    };
+    let get_residual = |log_correlation_length: f64| match fit(log_correlation_length) {
+        Ok((residual, _, _, _)) => residual,
+        Err(_) => f64::INFINITY,
+    };
+
+    let steps: usize = 400;
+    let lower_bound = 0.1f64.ln();
+    let upper_bound = (1000.0 * points[points.len() - 1].0).ln();
+    let step = (upper_bound - lower_bound) / steps as f64;
+    let best_step = (0..=steps)
+        .map(|index| (index, get_residual(lower_bound + index as f64 * step)))
+        .min_by(|(_, residual_0), (_, residual_1)| residual_0.total_cmp(residual_1))
+        .map(|(index, _)| index)
+        .unwrap_or(0);
+
+    let golden_ratio = (5f64.sqrt() - 1.0) / 2.0;
+    let mut lower = lower_bound + best_step.saturating_sub(1) as f64 * step;
+    let mut upper = lower_bound + (best_step + 1).min(steps) as f64 * step;
+
+    for _ in 0..100 {
+        let middle_0 = upper - golden_ratio * (upper - lower);
+        let middle_1 = lower + golden_ratio * (upper - lower);
+
+        if get_residual(middle_0) <= get_residual(middle_1) {
+            upper = middle_1;
+        } else {
+            lower = middle_0;
+        }
+    }
+
+    let log_correlation_length = (lower + upper) / 2.0;
+    let correlation_length = log_correlation_length.exp();
+    let (_, intercept, slope, standard_error) = fit(log_correlation_length)?;
+    let amplitude = slope * (shortest_length / correlation_length).exp();
+
+    Ok((intercept, amplitude, correlation_length, standard_error))
+}
+
+/// Gets the Shanks transformation of a sequence, which
+/// is one element shorter at each end.
+fn get_shanks_transformation(sequence: &[f64]) -> Vec<f64> {
+    sequence
+        .windows(3)
+        .map(|window| {
+            let difference = window[2] - window[1];
+            let second_difference = difference - (window[1] - window[0]);
+
+            if second_difference == 0.0 {
+                window[2]
+            } else {
+                window[2] - difference * difference / second_difference
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Gets the values of a function at the given lengths.
+    fn get_values<F>(lengths: &[u8], get_value: F) -> Vec<(u8, f32)> where F: Fn(f64) -> f64 {
+        lengths
+            .iter()
+            .map(|length| (*length, get_value(*length as f64) as f32))
+            .collect()
+    }
+
+    #[test]
+    fn power_law_fit_recovers_the_parameters() {
+        let values = get_values(&[6, 8, 10, 12, 14, 16], |length| -1.4 + 0.7 / (length * length));
+        let fit = fit_power_law(&values, 2.0).unwrap();
+
+        assert!((fit.extrapolation.value + 1.4).abs() < 1e-5, "{:?}", fit);
+        assert!((fit.amplitude - 0.7).abs() < 1e-3, "{:?}", fit);
+        assert!(fit.extrapolation.uncertainty < 1e-5, "{:?}", fit);
+    }
+
+    #[test]
+    fn exponential_fit_recovers_the_parameters() {
+        let values = get_values(&[4, 6, 8, 10, 12, 14], |length| 0.41 + 2.0 * (-length / 3.0).exp());
+        let fit = fit_exponential(&values).unwrap();
+
+        assert!((fit.extrapolation.value - 0.41).abs() < 1e-4, "{:?}", fit);
+        assert!((fit.amplitude - 2.0).abs() < 1e-2, "{:?}", fit);
+        assert!((fit.correlation_length - 3.0).abs() < 1e-2, "{:?}", fit);
+    }
+
+    #[test]
+    fn shanks_transformation_is_exact_for_exponentials() {
+        let values = get_values(&[4, 6, 8, 10, 12], |length| 0.41 + 2.0 * (-length / 3.0).exp());
+        let extrapolation = extrapolate_shanks(&values).unwrap();
+
+        assert!((extrapolation.value - 0.41).abs() < 1e-4, "{:?}", extrapolation);
+    }
+
+    #[test]
+    fn bst_recovers_the_limit_of_power_series() {
+        let values = get_values(&[6, 8, 10, 12, 14, 16], |length| {
+            -1.4 + 0.7 / length.powi(2) - 3.0 / length.powi(4)
+        });
+        let extrapolation = extrapolate_bst(&values, 2.0).unwrap();
+
+        assert!((extrapolation.value + 1.4).abs() < 1e-5, "{:?}", extrapolation);
+    }
+
+    #[test]
+    fn too_few_lengths_are_rejected() {
+        let values = get_values(&[6, 8], |length| 1.0 / length);
+
+        assert_eq!(
+            fit_power_law(&values, 1.0),
+            Err(Error::TooFewLengths {required: 3, found: 2}),
+        );
+    }
+}
\ No newline at end of file
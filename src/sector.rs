@@ -0,0 +1,496 @@
+//! This module contains the [`Sector`] struct, which
+//! binds a [`Model`] to the symmetry eigenvalues of a
+//! sector and the corresponding symmetry factors, and
+//! holds the power iteration algorithm.
+use crate::error::Error;
+use crate::hamiltonians::Implemented;
+use crate::model::Model;
+use crate::states::State;
+use crate::symmetries::{get_eq_class, get_symmetry_factors, time_reverse, Momentum, Parity, SymmetryFactors};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+/// A sector of a [`Model`] with given symmetry
+/// eigenvalues. Every [`Sector`] has an identifier,
+/// which the [`State`]s created in it are tagged with.
+/// It only depends on the symmetrized basis and the
+/// symmetry eigenvalues, so [`Sector`]s created twice,
+/// or in [`Model`]s that only differ in the number of
+/// threads or the seed, accept the same [`State`]s.
+pub struct Sector<'a> {
+    pub model: &'a Model,
+    pub time_reversal: Parity,
+    pub reflection: Parity,
+    pub translation: Momentum,
+    pub symmetry_factors: SymmetryFactors,
+    pub id: u64,
+}
+
+impl<'a> Sector<'a> {
+    /// Creates a [`Sector`]. The eigenvalues of symmetries
+    /// that are not used by the [`Model`] have no effect.
+    /// If translation is used, the momentum π requires an
+    /// even length.
+    pub fn new(
+        model: &'a Model,
+        time_reversal: Parity,
+        reflection: Parity,
+        translation: Momentum,
+    ) -> Result<Self, Error> {
+        if model.symmetries.translation && translation == Momentum::Pi && model.length % 2 != 0 {
+            return Err(Error::OddLengthMomentum(model.length));
+        }
+
+        let symmetry_factors = get_symmetry_factors(
+            &model.basis_states,
+            time_reversal,
+            reflection,
+            translation,
+        );
+        model.check_symmetry_factors(&symmetry_factors)?;
+
+        Ok(Self {
+            model,
+            time_reversal,
+            reflection,
+            translation,
+            symmetry_factors,
+            id: get_sector_id(model, time_reversal, reflection, translation),
+        })
+    }
+
+    /// Creates the [`Sector`] where all the symmetry 
+    /// eigenvalues are 1, which is the only sector of a 
+    /// [`Model`] without symmetries.
+    pub fn trivial(model: &'a Model) -> Result<Self, Error> {
+        Self::new(model, Parity::Even, Parity::Even, Momentum::Zero)
+    }
+
+    /// Creates every nonempty [`Sector`] of a [`Model`].
+    /// Only the eigenvalues of the symmetries that are 
+    /// used are distinguished, and the momentum π is only 
+    /// used for even lengths.
+    pub fn get_all(model: &'a Model) -> Result<Vec<Self>, Error> {
+        let parities = |used: bool| if used {&[Parity::Even, Parity::Odd][..]} else {&[Parity::Even][..]};
+        let momenta = if model.symmetries.translation && model.length % 2 == 0 {
+            &[Momentum::Zero, Momentum::Pi][..]
+        } else {
+            &[Momentum::Zero][..]
+        };
+        let mut sectors = Vec::new();
+
+        for time_reversal in parities(model.symmetries.time_reversal).iter().copied() {
+            for reflection in parities(model.symmetries.reflection.is_some()).iter().copied() {
+                for translation in momenta.iter().copied() {
+                    match Self::new(model, time_reversal, reflection, translation) {
+                        Ok(sector) => sectors.push(sector),
+                        Err(Error::EmptySector) => continue,
+                        Err(error) => return Err(error),
+                    }
+                }
+            }
+        }
+
+        Ok(sectors)
+    }
+
+    /// Gets the number of coefficients of the [`State`]s.
+    pub fn get_length(&self) -> usize {
+        self.model.basis_states.length
+    }
+
+    /// Creates the zero [`State`] of the [`Sector`].
+    pub fn from_zeros(&self) -> State {
+        let mut state = State::from_zeros(self.get_length());
+        state.sector = Some(self.id);
+
+        state
+    }
+
+    /// Creates a random [`State`] of the [`Sector`], 
+    /// which only depends on the seed.
+    pub fn from_random(&self, seed: u64) -> State {
+        let mut state = State::from_random(self.get_length(), seed);
+        state.sector = Some(self.id);
+
+        state
+    }
+
+    /// Projects a vector in the full basis onto the 
+    /// [`Sector`], which gives a trial [`State`].
+    pub fn from_full_state(&self, full_state: &[f32]) -> Result<State, Error> {
+        let model = self.model;
+        let max_value = (model.base as usize).pow(model.length as u32);
+
+        if full_state.len() != max_value {
+            return Err(Error::FullStateLengthMismatch {
+                expected: max_value,
+                found: full_state.len(),
+            });
+        }
+
+        self.project(|value| full_state[value])
+    }
+
+    /// Projects a product state onto the [`Sector`], which 
+    /// gives a trial [`State`]. The state of site n is 
+    /// given by `site_states[n]`, whose entry d is the 
+    /// coefficient of the digit d.
+    pub fn from_product_state(&self, site_states: &[Vec<f32>]) -> Result<State, Error> {
+        let model = self.model;
+
+        if site_states.len() != model.length as usize {
+            return Err(Error::ProductStateLengthMismatch {
+                expected: model.length as usize,
+                found: site_states.len(),
+            });
+        }
+
+        for site_state in site_states.iter() {
+            if site_state.len() != model.base as usize {
+                return Err(Error::SiteStateLengthMismatch {
+                    expected: model.base as usize,
+                    found: site_state.len(),
+                });
+            }
+        }
+
+        self.project(|value| {
+            let mut remaining_value = value;
+            let mut coefficient = 1.0;
+
+            for site_state in site_states.iter() {
+                coefficient *= site_state[remaining_value % model.base as usize];
+                remaining_value /= model.base as usize;
+            }
+
+            coefficient
+        })
+    }
+
+    /// Projects a vector in the full basis, given by its 
+    /// coefficients, onto the [`Sector`]. The coefficient 
+    /// of a representer r is (1/|G|) Σ_g χ(g) ψ(g r).
+    fn project<F>(&self, get_coefficient: F) -> Result<State, Error> where F: Fn(usize) -> f32 {
+        let model = self.model;
+        let mut state = self.from_zeros();
+
+        for (basis_state_index, representer_value) in model.basis_states.representers.iter().enumerate() {
+            if self.symmetry_factors.factors[basis_state_index] == 0.0 {
+                continue;
+            }
+
+            let coefficient: f32 = get_eq_class(*representer_value, model.base, model.length, &model.symmetries)
+                .into_iter()
+                .map(|(value, symmetry_sign)| self.symmetry_factors.get_sign(symmetry_sign) * get_coefficient(value))
+                .sum();
+
+            state.coefficients[basis_state_index] = coefficient / self.symmetry_factors.group_order as f32;
+        }
+
+        if state.coefficients.iter().all(|coefficient| *coefficient == 0.0) {
+            return Err(Error::VanishingNorm);
+        }
+
+        Ok(state)
+    }
+
+    /// Applies time reversal to a [`State`] of the 
+    /// [`Sector`], which gives a [`State`] of the 
+    /// [`Sector`] with the opposite total quantum spin in 
+    /// the z-direction and the same symmetry eigenvalues. 
+    /// For a Hamiltonian that commutes with time reversal, 
+    /// it maps eigenstates to eigenstates with the same 
+    /// eigenvalue.
+    pub fn invert_state(
+        &self,
+        state: &State,
+        inverted_sector: &Sector,
+    ) -> Result<State, Error> {
+        state.check_sector(self)?;
+
+        let model = self.model;
+        let inverted_model = inverted_sector.model;
+
+        if inverted_model.base != model.base
+            || inverted_model.length != model.length
+            || inverted_model.boundary != model.boundary
+            || inverted_model.symmetries != model.symmetries
+            || inverted_model.total_s_z != model.total_s_z.map(|total_s_z| total_s_z.inverted())
+            || inverted_sector.symmetry_factors.eigenvalues != self.symmetry_factors.eigenvalues
+        {
+            return Err(Error::IncompatibleSectors);
+        }
+
+        let mut inverted_state = inverted_sector.from_zeros();
+
+        for (basis_state_index, representer_value) in model.basis_states.representers.iter().enumerate() {
+            if self.symmetry_factors.factors[basis_state_index] == 0.0 {
+                continue;
+            }
+
+            let inverted_value = time_reverse(*representer_value, model.base, model.length);
+            let Some((inverted_basis_state_index, symmetry_sign)) = inverted_model.find_representer(inverted_value) else {
+                return Err(Error::IncompatibleSectors);
+            };
+
+            inverted_state.coefficients[inverted_basis_state_index] = state.coefficients[basis_state_index]
+                * inverted_sector.symmetry_factors.get_sign(symmetry_sign);
+        }
+
+        Ok(inverted_state)
+    }
+
+    /// Executes the power iteration with the shifted 
+    /// Hamiltonian H-shift, starting from `initial_state`. 
+    /// The lower eigenstates must be normalized, and they 
+    /// are deflated by shifting their eigenvalues to 0.
+    fn power_iterate<T: Implemented>(
+        &self,
+        hamiltonian: &T,
+        iterations: u32,
+        initial_state: State,
+        lower_eigenpairs: &[(State, f32)],
+    ) -> Result<State, Error> {
+        let threads = self.model.threads;
+        let mut state_0 = initial_state;
+        state_0.sector = Some(self.id);
+        let mut state_1 = self.from_zeros();
+        // The shift is strictly larger than every eigenvalue,
+        // so the iteration does not vanish in sectors where
+        // the largest eigenvalue is the only one, such as
+        // the fully polarized sectors.
+        let shift = hamiltonian.get_max_eigenenergy(self.model) + 1.0;
+
+        for _ in 0..iterations/2 {
+            hamiltonian.apply(&state_0, &mut state_1, self)?;
+            state_1.subtract(&state_0, shift, threads)?;
+
+            for (lower_eigenstate, lower_eigenenergy) in lower_eigenpairs.iter() {
+                let overlap = lower_eigenstate.dot(&state_0, self)?;
+                state_1.subtract(lower_eigenstate, (lower_eigenenergy - shift) * overlap, threads)?;
+            }
+
+            state_0.clear();
+
+            hamiltonian.apply(&state_1, &mut state_0, self)?;
+            state_0.subtract(&state_1, shift, threads)?;
+
+            for (lower_eigenstate, lower_eigenenergy) in lower_eigenpairs.iter() {
+                let overlap = lower_eigenstate.dot(&state_1, self)?;
+                state_0.subtract(lower_eigenstate, (lower_eigenenergy - shift) * overlap, threads)?;
+            }
+
+            state_1.clear();
+            state_0.normalize(self)?;
+        }
+
+        Ok(state_0)
+    }
+
+    /// Finds the eigenvector of the Hamtilonian with
+    /// the smallest eigenvalue in the [`Sector`]. The 
+    /// power iteration starts from a random [`State`] 
+    /// given by the seed of the [`Model`] and the number 
+    /// of lower eigenpairs, so the results are reproducible.
+    pub fn find_eigenstate<T: Implemented>(
+        &self,
+        hamiltonian: &T,
+        iterations: u32,
+        lower_eigenpairs: &[(State, f32)],
+    ) -> Result<(State, f32), Error> {
+        let seed = self.model.seed.wrapping_add(lower_eigenpairs.len() as u64);
+
+        self.find_eigenstate_from(hamiltonian, iterations, self.from_random(seed), lower_eigenpairs)
+    }
+
+    /// Finds the eigenvector of the Hamtilonian with
+    /// the smallest eigenvalue in the [`Sector`], starting
+    /// the power iteration from `initial_state` instead of 
+    /// a random [`State`]. It must not be orthogonal to the 
+    /// eigenvector.
+    pub fn find_eigenstate_from<T: Implemented>(
+        &self,
+        hamiltonian: &T,
+        iterations: u32,
+        initial_state: State,
+        lower_eigenpairs: &[(State, f32)],
+    ) -> Result<(State, f32), Error> {
+        initial_state.check_sector(self)?;
+
+        for (lower_eigenstate, _) in lower_eigenpairs.iter() {
+            lower_eigenstate.check_sector(self)?;
+        }
+
+        let eigenstate = self.power_iterate(hamiltonian, iterations, initial_state, lower_eigenpairs)?;
+        let mut eigenstate_times_eigenenergy = self.from_zeros();
+        hamiltonian.apply(&eigenstate, &mut eigenstate_times_eigenenergy, self)?;
+        let eigenenergy = eigenstate.dot(&eigenstate_times_eigenenergy, self)?;
+
+        Ok((eigenstate, eigenenergy))
+    }
+}
+
+/// Gets the identifier of a [`Sector`] from the
+/// symmetrized basis of the [`Model`] and the eigenvalues
+/// of the symmetries that are used.
+fn get_sector_id(
+    model: &Model,
+    time_reversal: Parity,
+    reflection: Parity,
+    translation: Momentum,
+) -> u64 {
+    let symmetries = &model.symmetries;
+    let mut hasher = DefaultHasher::new();
+    (model.base, model.length, model.total_s_z, symmetries).hash(&mut hasher);
+    (
+        if symmetries.time_reversal {time_reversal} else {Parity::Even},
+        if symmetries.reflection.is_some() {reflection} else {Parity::Even},
+        if symmetries.translation {translation} else {Momentum::Zero},
+    ).hash(&mut hasher);
+
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::basis::SzSector;
+    use crate::hamiltonians::{Hamiltonian, AFH, AKLT};
+    use crate::symmetries::Reflection;
+    use crate::testing::{get_eigenvalues, get_model, get_sector_matrix};
+
+    /// Gets the energy and the norm of the residual
+    /// H|ψ⟩ - E|ψ⟩ of a normalized [`State`].
+    fn get_energy_and_residual<T: Implemented>(hamiltonian: &T, state: &State, sector: &Sector) -> (f32, f32) {
+        let mut residual = sector.from_zeros();
+        hamiltonian.apply(state, &mut residual, sector).unwrap();
+        let energy = state.dot(&residual, sector).unwrap();
+        residual.subtract(state, energy, 1).unwrap();
+
+        (energy, residual.dot(&residual, sector).unwrap().sqrt())
+    }
+
+    /// Compares the lowest eigenvalues found by deflation
+    /// with the brute-force eigenvalues of a [`Sector`].
+    fn check_excited_states<T: Implemented>(hamiltonian: &T, sector: &Sector, levels: usize) {
+        let expected = get_eigenvalues(&get_sector_matrix(hamiltonian, sector));
+        let mut eigenpairs = Vec::new();
+
+        for _ in 0..levels {
+            let eigenpair = sector.find_eigenstate(hamiltonian, 2000, &eigenpairs).unwrap();
+            eigenpairs.push(eigenpair);
+        }
+
+        for (level, (_, energy)) in eigenpairs.iter().enumerate() {
+            assert!((*energy as f64 - expected[level]).abs() < 1e-3, "level {}: {} != {}", level, energy, expected[level]);
+        }
+    }
+
+    #[test]
+    fn excited_states_agree_with_brute_force() {
+        let model = Model::new(3, 6, SzSector::new(0)).unwrap();
+
+        for sector in Sector::get_all(&model).unwrap() {
+            let levels = sector.symmetry_factors.factors.iter().filter(|factor| **factor != 0.0).count().min(3);
+
+            check_excited_states(&Hamiltonian::<AKLT>::new(1.0), &sector, levels);
+            check_excited_states(&Hamiltonian::<AFH>::new(1.0), &sector, levels);
+        }
+    }
+
+    #[test]
+    fn trial_states_of_the_wrong_length_are_rejected() {
+        let model = Model::new(3, 4, SzSector::new(0)).unwrap();
+        let sector = Sector::trivial(&model).unwrap();
+        let site_state = vec![0.0, 1.0, 0.0];
+
+        assert_eq!(
+            sector.from_full_state(&[1.0; 80]).err(),
+            Some(Error::FullStateLengthMismatch {expected: 81, found: 80}),
+        );
+        assert_eq!(
+            sector.from_product_state(&vec![site_state.clone(); 3]).err(),
+            Some(Error::ProductStateLengthMismatch {expected: 4, found: 3}),
+        );
+        assert_eq!(
+            sector.from_product_state(&[site_state.clone(), site_state.clone(), site_state, vec![1.0, 0.0]]).err(),
+            Some(Error::SiteStateLengthMismatch {expected: 3, found: 2}),
+        );
+    }
+
+    #[test]
+    fn full_states_are_projected_back() {
+        let model = Model::new(3, 6, SzSector::new(0)).unwrap();
+
+        for sector in Sector::get_all(&model).unwrap() {
+            let mut state = sector.from_random(3);
+
+            for (coefficient, symmetry_factor) in state.coefficients.iter_mut().zip(sector.symmetry_factors.factors.iter()) {
+                if *symmetry_factor == 0.0 {
+                    *coefficient = 0.0;
+                }
+            }
+
+            let projected_state = sector.from_full_state(&state.get_full_state(&sector).unwrap()).unwrap();
+
+            for (found, expected) in projected_state.coefficients.iter().zip(state.coefficients.iter()) {
+                assert!((found - expected).abs() < 1e-5);
+            }
+        }
+    }
+
+    #[test]
+    fn states_are_tagged_with_the_basis_and_the_eigenvalues() {
+        let model = Model::new(3, 6, SzSector::new(0)).unwrap();
+        let same_model = Model::new(3, 6, SzSector::new(0)).unwrap();
+        let other_model = Model::new(3, 6, SzSector::new(1)).unwrap();
+        let plain_model = Model::new_plain(3, 6, Some(SzSector::new(0))).unwrap();
+        let sector = Sector::new(&model, Parity::Even, Parity::Odd, Momentum::Pi).unwrap();
+        let state = sector.from_random(1);
+
+        let same_sector = Sector::new(&model, Parity::Even, Parity::Odd, Momentum::Pi).unwrap();
+        assert!(state.check_sector(&same_sector).is_ok());
+        let same_sector = Sector::new(&same_model, Parity::Even, Parity::Odd, Momentum::Pi).unwrap();
+        assert!(state.check_sector(&same_sector).is_ok());
+
+        let other_sector = Sector::new(&model, Parity::Even, Parity::Even, Momentum::Pi).unwrap();
+        assert!(matches!(state.check_sector(&other_sector), Err(Error::SectorMismatch {..})));
+        let other_sector = Sector::new(&other_model, Parity::Even, Parity::Odd, Momentum::Pi).unwrap();
+        assert_ne!(other_sector.id, sector.id);
+
+        // The eigenvalues of symmetries that are not used
+        // do not distinguish sectors.
+        let plain_sector = Sector::trivial(&plain_model).unwrap();
+        let other_plain_sector = Sector::new(&plain_model, Parity::Odd, Parity::Odd, Momentum::Pi).unwrap();
+        assert_eq!(plain_sector.id, other_plain_sector.id);
+        assert_ne!(plain_sector.id, sector.id);
+    }
+
+    #[test]
+    fn inverted_eigenstates_are_eigenstates() {
+        let hamiltonian = Hamiltonian::<AKLT>::new(1.0);
+        let model = get_model(3, 6, SzSector::new(1), true, Some(Reflection::Bond), true);
+        let inverted_model = get_model(3, 6, SzSector::new(-1), true, Some(Reflection::Bond), true);
+        let inverted_sectors = Sector::get_all(&inverted_model).unwrap();
+
+        for sector in Sector::get_all(&model).unwrap() {
+            let (mut eigenstate, eigenenergy) = sector.find_eigenstate(&hamiltonian, 2000, &[]).unwrap();
+            eigenstate.normalize(&sector).unwrap();
+            let (_, residual) = get_energy_and_residual(&hamiltonian, &eigenstate, &sector);
+            let inverted_sector = inverted_sectors
+                .iter()
+                .find(|inverted_sector| {
+                    (inverted_sector.reflection, inverted_sector.translation) == (sector.reflection, sector.translation)
+                })
+                .unwrap();
+            let inverted_state = sector.invert_state(&eigenstate, inverted_sector).unwrap();
+            let (inverted_energy, inverted_residual) = get_energy_and_residual(&hamiltonian, &inverted_state, inverted_sector);
+
+            assert!((inverted_state.dot(&inverted_state, inverted_sector).unwrap() - 1.0).abs() < 1e-4);
+            assert!((inverted_energy - eigenenergy).abs() < 1e-4, "{} != {}", inverted_energy, eigenenergy);
+            assert!(residual < 1e-2 && (inverted_residual - residual).abs() < 1e-4, "{} {}", residual, inverted_residual);
+            assert_eq!(sector.invert_state(&eigenstate, &sector).err(), Some(Error::IncompatibleSectors));
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,140 @@
+//! This module contains the fast path used for spin-1/2
+//! chains. The basis states are stored as the bits of a
+//! word, so the total quantum spin in the z-direction is
+//! a population count and the symmetries are bit
+//! operations.
+use crate::symmetries::{get_translation_sign, Reflection, Symmetries};
+
+/// Iterates over the basis states with a given number of
+/// up spins in increasing order.
+pub fn get_configurations(
+    length: u8,
+    up_spins: usize,
+) -> impl Iterator<Item = usize> {
+    let max_value = get_mask(length);
+    let first_value = if up_spins <= length as usize {
+        Some(get_mask(up_spins as u8))
+    } else {
+        None
+    };
+
+    std::iter::successors(first_value, move |value| {
+        if *value == 0 {
+            return None;
+        }
+
+        // Gosper's hack: the next larger integer with the
+        // same population count.
+        let lowest_bit = value & value.wrapping_neg();
+        let ripple = value.checked_add(lowest_bit)?;
+        let next_value = ripple | (((value ^ ripple) >> 2) / lowest_bit);
+
+        if next_value <= max_value {
+            Some(next_value)
+        } else {
+            None
+        }
+    })
+}
+
+/// Visits all the basis states that are equivalent
+/// under the symmetries, until `function` returns
+/// `false`. The symmetries must leave the sector 
+/// invariant.
+pub fn for_each_equivalent<F>(
+    value: usize,
+    length: u8,
+    symmetries: &Symmetries,
+    mut function: F,
+) where F: FnMut(usize, [bool; 3]) -> bool {
+    let mask = get_mask(length);
+    let reflected_value = match symmetries.reflection {
+        Some(Reflection::Site) => rotate_value(reflect_value(value, length), length),
+        _ => reflect_value(value, length),
+    };
+    let translations = if symmetries.translation {length} else {1};
+    let time_reversed_reflected_values = [
+        (value, [false, false, false]),
+        (!value & mask, [true, false, false]),
+        (reflected_value, [false, true, false]),
+        (!reflected_value & mask, [true, true, false]),
+    ];
+
+    for (time_reversed_reflected_value, mut symmetry_sign) in time_reversed_reflected_values {
+        if symmetry_sign[0] && !symmetries.time_reversal {
+            continue;
+        }
+
+        if symmetry_sign[1] && symmetries.reflection.is_none() {
+            continue;
+        }
+
+        let mut translation = time_reversed_reflected_value;
+
+        for translate in 0..translations {
+            symmetry_sign[2] = get_translation_sign(translate as usize);
+
+            if !function(translation, symmetry_sign) {
+                return;
+            }
+
+            translation = translate_value(translation, length);
+        }
+    }
+}
+
+/// Gets the word with the lowest `length` bits set.
+fn get_mask(length: u8) -> usize {
+    if length as u32 >= usize::BITS {
+        usize::MAX
+    } else {
+        (1 << length) - 1
+    }
+}
+
+/// Reflects a basis state.
+fn reflect_value(value: usize, length: u8) -> usize {
+    if length == 0 {
+        return 0;
+    }
+
+    value.reverse_bits() >> (usize::BITS - length as u32)
+}
+
+/// Translates a basis state by one site in the 
+/// opposite direction of [`translate_value`].
+fn rotate_value(value: usize, length: u8) -> usize {
+    ((value << 1) & get_mask(length)) | (value >> (length - 1))
+}
+
+/// Translates a basis state by one site.
+fn translate_value(value: usize, length: u8) -> usize {
+    (value >> 1) | ((value & 1) << (length - 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn configurations_agree_with_filtered_enumeration() {
+        for length in 1..=12 {
+            for up_spins in 0..=length as usize + 1 {
+                let expected: Vec<usize> = (0..1 << length)
+                    .filter(|value: &usize| value.count_ones() as usize == up_spins)
+                    .collect();
+
+                assert_eq!(get_configurations(length, up_spins).collect::<Vec<usize>>(), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn configurations_of_a_full_word() {
+        let length = usize::BITS as u8;
+
+        assert_eq!(get_configurations(length, length as usize).collect::<Vec<usize>>(), vec![usize::MAX]);
+        assert_eq!(get_configurations(length, 0).collect::<Vec<usize>>(), vec![0]);
+        assert_eq!(get_configurations(length, length as usize - 1).count(), length as usize);
+    }
+}
\ No newline at end of file
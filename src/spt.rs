@@ -0,0 +1,169 @@
+//! This module contains the helpers used to detect
+//! symmetry-protected topological phases through the
+//! π-rotations exp(iπS^x_tot) and exp(iπS^z_tot), which
+//! generate the group D2 = Z2×Z2. In the Haldane phase,
+//! the edges of an open chain carry projective
+//! representations of D2, so the nearly degenerate
+//! ground manifold of an open chain carries all four
+//! irreducible representations of D2.
+use crate::basis::SzSector;
+use crate::error::Error;
+use crate::hamiltonians::Implemented;
+use crate::model::{Boundary, ModelBuilder};
+use crate::operators::{get_eigenvalue, Operator};
+use crate::sector::Sector;
+use crate::symmetries::{get_pi_rotation_phase, Momentum, Parity};
+
+/// A state of the ground manifold of an open chain
+/// together with its π-rotation eigenvalues, which
+/// label an irreducible representation of D2.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct GroundManifoldState {
+    pub energy: f32,
+    /// The eigenvalue of exp(iπS^x_tot).
+    pub x_eigenvalue: f32,
+    /// The eigenvalue of exp(iπS^z_tot).
+    pub z_eigenvalue: f32,
+}
+
+/// Finds the lowest states of an open chain in the
+/// sectors S^z=0 and S^z=±1, arranged by energy, which
+/// is the ground manifold of chains with spin-1/2 edge
+/// states. In the sector S^z=0, time reversal gives the
+/// eigenvalue of exp(iπS^x_tot). The lowest states with
+/// S^z=1 and S^z=-1 are degenerate, and their sum and
+/// difference have opposite eigenvalues of
+/// exp(iπS^x_tot). The π-rotations must be real, that is
+/// S*length must be an integer.
+pub fn get_ground_manifold<T: Implemented>(
+    hamiltonian: &T,
+    base: u8,
+    length: u8,
+    iterations: u32,
+    threads: usize,
+) -> Result<Vec<GroundManifoldState>, Error> {
+    let phase = get_pi_rotation_phase(base, length).ok_or(Error::NonRealOperator)?;
+    let mut ground_manifold = Vec::with_capacity(4);
+    let model = ModelBuilder::new(base, length)
+        .total_s_z(Some(SzSector::new(0)))
+        .time_reversal(true)
+        .boundary(Boundary::Open)
+        .threads(threads)
+        .build()?;
+
+    for time_reversal in [Parity::Even, Parity::Odd] {
+        let sector = match Sector::new(&model, time_reversal, Parity::Even, Momentum::Zero) {
+            Ok(sector) => sector,
+            Err(Error::EmptySector) => continue,
+            Err(error) => return Err(error),
+        };
+        let (state, energy) = sector.find_eigenstate(hamiltonian, iterations, &[])?;
+
+        ground_manifold.push(GroundManifoldState {
+            energy,
+            x_eigenvalue: get_eigenvalue(&state, &sector, Operator::PiRotationX)?,
+            z_eigenvalue: get_eigenvalue(&state, &sector, Operator::PiRotationZ)?,
+        });
+    }
+
+    let total_s_z = SzSector::new(1);
+
+    if total_s_z.is_valid(base, length) {
+        let model = ModelBuilder::new(base, length)
+            .total_s_z(Some(total_s_z))
+            .boundary(Boundary::Open)
+            .threads(threads)
+            .build()?;
+        let sector = Sector::trivial(&model)?;
+        let (state, energy) = sector.find_eigenstate(hamiltonian, iterations, &[])?;
+        let z_eigenvalue = get_eigenvalue(&state, &sector, Operator::PiRotationZ)?;
+
+        for x_eigenvalue in [phase, -phase] {
+            ground_manifold.push(GroundManifoldState {energy, x_eigenvalue, z_eigenvalue});
+        }
+    }
+
+    ground_manifold.sort_by(|state_0, state_1| state_0.energy.total_cmp(&state_1.energy));
+
+    Ok(ground_manifold)
+}
+
+/// Counts the irreducible representations of D2 carried
+/// by the states of the ground manifold whose energy is
+/// at most `energy_window` above the lowest energy. If it
+/// is 4, the edge states transform projectively.
+pub fn count_irreps(
+    ground_manifold: &[GroundManifoldState],
+    energy_window: f32,
+) -> usize {
+    let Some(lowest_state) = ground_manifold.first() else {
+        return 0;
+    };
+    let mut irreps: Vec<(bool, bool)> = Vec::with_capacity(4);
+
+    for state in ground_manifold.iter() {
+        let irrep = (state.x_eigenvalue < 0.0, state.z_eigenvalue < 0.0);
+
+        if state.energy - lowest_state.energy <= energy_window && !irreps.contains(&irrep) {
+            irreps.push(irrep);
+        }
+    }
+
+    irreps.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hamiltonians::{Hamiltonian, AFH, AKLT};
+    use crate::testing::Perturbed;
+
+    #[test]
+    fn open_aklt_chain_has_a_fourfold_ground_manifold() {
+        let ground_manifold = get_ground_manifold(&Hamiltonian::<AKLT>::new(1.0), 3, 6, 1000, 1).unwrap();
+
+        assert_eq!(ground_manifold.len(), 4);
+        assert!(ground_manifold[3].energy - ground_manifold[0].energy < 1e-3, "{:?}", ground_manifold);
+        assert_eq!(count_irreps(&ground_manifold, 1e-3), 4);
+    }
+
+    #[test]
+    fn trivial_phases_have_a_single_irrep() {
+        // The large-D phase of the AFH chain.
+        let mut large_d = Perturbed::new(Hamiltonian::<AFH>::new(1.0));
+        large_d.anisotropies = vec![3.0; 6];
+        // The AFH chain with strong bonds between the sites
+        // 2n and 2n+1, which form singlets.
+        let mut dimerized = Perturbed::new(Hamiltonian::<AFH>::new(1.0));
+        dimerized.exchanges = vec![3.0, 0.0, 3.0, 0.0, 3.0];
+
+        for ground_manifold in [
+            get_ground_manifold(&Hamiltonian::<AKLT>::new(0.0), 3, 6, 1000, 1).unwrap(),
+            get_ground_manifold(&large_d, 3, 6, 1000, 1).unwrap(),
+            get_ground_manifold(&dimerized, 3, 6, 1000, 1).unwrap(),
+        ] {
+            assert!(ground_manifold[1].energy - ground_manifold[0].energy > 0.1, "{:?}", ground_manifold);
+            assert_eq!(count_irreps(&ground_manifold, 0.01), 1);
+        }
+    }
+
+    #[test]
+    fn irreps_are_counted_within_the_window() {
+        let state = |energy: f32, x_eigenvalue: f32, z_eigenvalue: f32| GroundManifoldState {
+            energy,
+            x_eigenvalue,
+            z_eigenvalue,
+        };
+        let ground_manifold = [
+            state(-1.0, 1.0, 1.0),
+            state(-1.0, 1.0, 1.0),
+            state(-0.9, -1.0, 1.0),
+            state(0.0, 1.0, -1.0),
+        ];
+
+        assert_eq!(count_irreps(&[], 1.0), 0);
+        assert_eq!(count_irreps(&ground_manifold, 0.05), 1);
+        assert_eq!(count_irreps(&ground_manifold, 0.5), 2);
+        assert_eq!(count_irreps(&ground_manifold, 2.0), 3);
+    }
+}
\ No newline at end of file
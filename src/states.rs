@@ -1,12 +1,19 @@
 //! This module contains the [`State`] struct,
 //! which is a symmetrized version of a vector.
-use crate::basis::BasisStates;
-use rand::random;
-use std::ops::{Mul, MulAssign, SubAssign};
+use crate::error::Error;
+use crate::parallel::{for_each_chunk, sum_chunks};
+use crate::sector::Sector;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use std::ops::{Mul, MulAssign};
 
 /// Symmetrized version of a vector.
+#[derive(Clone, PartialEq, Debug)]
 pub struct State {
     pub coefficients: Vec<f32>,
+    /// The identifier of the [`Sector`] the [`State`] 
+    /// belongs to, or `None` if it is not tagged.
+    pub sector: Option<u64>,
 }
 
 impl State {
@@ -14,69 +21,154 @@ impl State {
     pub fn from_zeros(basis_states_length: usize) -> Self {
         let coefficients = vec![0.0; basis_states_length];
 
-        Self {coefficients}
+        Self {coefficients, sector: None}
     }
 
-    /// Creates a random [`State`].
-    pub fn from_random(basis_states_length: usize) -> Self {
+    /// Creates a random [`State`] with coefficients drawn 
+    /// uniformly from [-1, 1). The coefficients only depend 
+    /// on the seed.
+    pub fn from_random(basis_states_length: usize, seed: u64) -> Self {
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
         let coefficients = (0..basis_states_length)
-            .map(|_| random())
+            .map(|_| rng.gen_range(-1.0..1.0))
             .collect();
 
-        Self {coefficients}
+        Self {coefficients, sector: None}
+    }
+
+    /// Checks that the [`State`] has a given number of 
+    /// coefficients.
+    pub fn check_length(&self, basis_states_length: usize) -> Result<(), Error> {
+        if self.coefficients.len() != basis_states_length {
+            return Err(Error::DimensionMismatch {
+                expected: basis_states_length,
+                found: self.coefficients.len(),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Checks that the [`State`] can belong to a 
+    /// [`Sector`], that is it has one coefficient per 
+    /// representer and it is untagged or tagged with 
+    /// the [`Sector`].
+    pub fn check_sector(&self, sector: &Sector) -> Result<(), Error> {
+        self.check_length(sector.get_length())?;
+        self.check_same_sector(Some(sector.id))
+    }
+
+    /// Checks that the [`State`] is not tagged with 
+    /// another sector.
+    fn check_same_sector(&self, sector: Option<u64>) -> Result<(), Error> {
+        match (self.sector, sector) {
+            (Some(found), Some(expected)) if found != expected => {
+                Err(Error::SectorMismatch {expected, found})
+            },
+            _ => Ok(()),
+        }
     }
 
     /// Inner product between this [`State`] and another.
+    /// With more than one thread, the partial sums of 
+    /// contiguous chunks are added in a fixed order.
     pub fn dot(
         &self,
         state: &State,
-        length: u8,
-        basis_states: &BasisStates,
-        symmetry_factors: &Vec<f32>
-    ) -> f32 {
-        let mut dot_product = 0.0;
+        sector: &Sector,
+    ) -> Result<f32, Error> {
+        self.check_sector(sector)?;
+        state.check_sector(sector)?;
+
+        let symmetry_factors = &sector.symmetry_factors;
 
-        for (index, (coefficient, representer)) in self.coefficients.iter().zip(basis_states.representers.iter()).enumerate() {
-            let symmetry_factor = symmetry_factors[representer.value];
+        let dot_product = sum_chunks(self.coefficients.len(), sector.model.threads, |chunk| {
+            let mut dot_product = 0.0;
 
-            if symmetry_factor != 0.0 {
-                dot_product += coefficient * state.coefficients[index] / symmetry_factor
+            for index in chunk {
+                let symmetry_factor = symmetry_factors.factors[index];
+
+                if symmetry_factor != 0.0 {
+                    dot_product += self.coefficients[index] * state.coefficients[index] / symmetry_factor
+                }
             }
-        }
 
-        dot_product * 4.0 * length as f32
+            dot_product
+        });
+
+        Ok(dot_product * symmetry_factors.group_order as f32)
     }
 
     /// Gets the norm of the [`State`].
     fn get_norm(
         &self,
-        length: u8,
-        basis_states: &BasisStates,
-        symmetry_factors: &Vec<f32>,
-    ) -> f32 {
-        self.dot(self, length, basis_states, symmetry_factors).sqrt()
+        sector: &Sector,
+    ) -> Result<f32, Error> {
+        Ok(self.dot(self, sector)?.sqrt())
     }
 
     /// Gets the infinity norm of the [`State`].
-    pub fn get_infinity_norm(&self) -> f32 {
-        self.coefficients.iter().max_by(|a, b| a.abs().total_cmp(&b.abs())).unwrap().abs()
+    pub fn get_infinity_norm(&self) -> Result<f32, Error> {
+        self.coefficients
+            .iter()
+            .max_by(|a, b| a.abs().total_cmp(&b.abs()))
+            .map(|coefficient| coefficient.abs())
+            .ok_or(Error::EmptyState)
     }
 
     /// Normalizes the [`State`].
     pub fn normalize(
         &mut self,
-        length: u8,
-        basis_states: &BasisStates,
-        symmetry_factors: &Vec<f32>,
-    ) {
-        let norm = self.get_norm(length, basis_states, symmetry_factors);
-        *self *= 1.0 / norm;
+        sector: &Sector,
+    ) -> Result<(), Error> {
+        let norm = self.get_norm(sector)?;
+
+        if norm == 0.0 || !norm.is_finite() {
+            return Err(Error::VanishingNorm);
+        }
+
+        let scaler = 1.0 / norm;
+
+        for_each_chunk(&mut self.coefficients, sector.model.threads, |_, coefficients| {
+            for coefficient in coefficients.iter_mut() {
+                *coefficient *= scaler;
+            }
+        });
+
+        Ok(())
+    }
+
+    /// Subtracts a multiple of another [`State`] from 
+    /// this [`State`].
+    pub fn subtract(
+        &mut self,
+        state: &State,
+        scaler: f32,
+        threads: usize,
+    ) -> Result<(), Error> {
+        state.check_length(self.coefficients.len())?;
+        state.check_same_sector(self.sector)?;
+
+        for_each_chunk(&mut self.coefficients, threads, |offset, coefficients| {
+            for (index, coefficient) in coefficients.iter_mut().enumerate() {
+                *coefficient -= state.coefficients[offset + index] * scaler;
+            }
+        });
+
+        Ok(())
     }
 
     /// Normalizes the [`State`] using the infinity norm.
-    pub fn infinity_normalize(&mut self) {
-        let infinity_norm = self.get_infinity_norm();
-        *self *= 1.0 / infinity_norm
+    pub fn infinity_normalize(&mut self) -> Result<(), Error> {
+        let infinity_norm = self.get_infinity_norm()?;
+
+        if infinity_norm == 0.0 || !infinity_norm.is_finite() {
+            return Err(Error::VanishingNorm);
+        }
+
+        *self *= 1.0 / infinity_norm;
+
+        Ok(())
     }
 
     /// Sets the [`State`] to zero.
@@ -89,22 +181,26 @@ impl State {
     /// Converts the [`State`] into a regular vector.
     pub fn get_full_state(
         &self,
-        basis_states: &BasisStates,
-        symmetry_factors: &Vec<f32>
-    ) -> Vec<f32> {
-        let mut full_state = Vec::with_capacity(symmetry_factors.len());
-
-        for (index, basis_state_index) in basis_states.representer_map.iter().enumerate() {
-            let symmetry_factor = symmetry_factors[index];
-
-            if symmetry_factor != 0.0 {
-                full_state.push(self.coefficients[*basis_state_index])
-            } else {
-                full_state.push(0.0)
+        sector: &Sector,
+    ) -> Result<Vec<f32>, Error> {
+        self.check_sector(sector)?;
+
+        let model = sector.model;
+        let symmetry_factors = &sector.symmetry_factors;
+
+        let max_value = (model.base as usize).pow(model.length as u32);
+        let mut full_state = Vec::with_capacity(max_value);
+
+        for value in 0..max_value {
+            match model.find_representer(value) {
+                Some((basis_state_index, symmetry_sign)) if symmetry_factors.factors[basis_state_index] != 0.0 => {
+                    full_state.push(self.coefficients[basis_state_index] * symmetry_factors.get_sign(symmetry_sign))
+                },
+                _ => full_state.push(0.0),
             }
         }
         
-        full_state
+        Ok(full_state)
     }
 }
 
@@ -118,7 +214,7 @@ impl Mul<f32> for &State {
             *coefficient *= scaler;
         }
 
-        State {coefficients}
+        State {coefficients, sector: self.sector}
     }
 }
 
@@ -131,11 +227,38 @@ impl MulAssign<f32> for State {
     }
 }
 
-impl SubAssign for State {
-    /// Subtracts two [`State`]s.
-    fn sub_assign(&mut self, state: Self) {
-        for (index, coefficient) in self.coefficients.iter_mut().enumerate() {
-            *coefficient -= state.coefficients[index];
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::basis::SzSector;
+    use crate::model::Model;
+    use crate::sector::Sector;
+    use crate::symmetries::{Momentum, Parity};
+
+    #[test]
+    fn subtraction_checks_the_length_and_the_sector() {
+        let model = Model::new(3, 4, SzSector::new(0)).unwrap();
+        let sector_0 = Sector::new(&model, Parity::Even, Parity::Even, Momentum::Zero).unwrap();
+        let sector_1 = Sector::new(&model, Parity::Even, Parity::Even, Momentum::Pi).unwrap();
+        let mut state = sector_0.from_random(1);
+
+        assert_eq!(
+            state.subtract(&State::from_zeros(1), 1.0, 1),
+            Err(Error::DimensionMismatch {expected: state.coefficients.len(), found: 1}),
+        );
+        assert!(matches!(
+            state.subtract(&sector_1.from_random(2), 1.0, 1),
+            Err(Error::SectorMismatch {..}),
+        ));
+
+        let other_state = sector_0.from_random(2);
+        let expected: Vec<f32> = state.coefficients
+            .iter()
+            .zip(other_state.coefficients.iter())
+            .map(|(coefficient, other_coefficient)| coefficient - 2.0 * other_coefficient)
+            .collect();
+        state.subtract(&other_state, 2.0, 1).unwrap();
+
+        assert_eq!(state.coefficients, expected);
     }
 }
\ No newline at end of file
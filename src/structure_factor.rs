@@ -0,0 +1,341 @@
+//! This module contains the dynamical structure factor
+//! S(q, ω) = Σ_n |⟨n|A_q|0⟩|^2 δ(ω - E_n + E_0), which is
+//! measured by inelastic neutron scattering, for the
+//! operators A_q = L^{-1/2} Σ_j e^{iqj} S^a_j. It is
+//! calculated by the continued-fraction method: the
+//! Lanczos recursion starting from A_q|0⟩ gives
+//! S(q, ω) = -Im⟨A_q|(ω + E_0 + iη - H)^-1|A_q⟩/π, where η
+//! is the broadening of the peaks.
+use crate::error::Error;
+use crate::evolution::ComplexState;
+use crate::hamiltonians::Implemented;
+use crate::lanczos::{get_lanczos_coefficients, Tridiagonal};
+use crate::sector::Sector;
+use crate::states::State;
+use num_complex::Complex64;
+use std::f64::consts::PI;
+
+/// A spin operator of a site.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SpinOperator {
+    /// S^z, which keeps the total quantum spin in the
+    /// z-direction.
+    Z,
+    /// S^+, which raises the total quantum spin in the
+    /// z-direction by one.
+    Plus,
+}
+
+/// The continued fraction of a dynamical structure
+/// factor, which gives the spectral function for any
+/// grid of frequencies and broadening.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ContinuedFraction {
+    /// The Lanczos coefficients starting from A_q|0⟩.
+    pub tridiagonal: Tridiagonal,
+    pub ground_state_energy: f32,
+    /// The total weight ⟨A_q|A_q⟩, which is the integral
+    /// of the spectral function over all frequencies.
+    pub weight: f32,
+}
+
+/// A spectral function on a grid of frequencies.
+#[derive(Clone, PartialEq, Debug)]
+pub struct SpectralFunction {
+    /// The frequencies ω, which are energies above the
+    /// ground state energy.
+    pub frequencies: Vec<f32>,
+    /// The spectral function at every frequency.
+    pub intensities: Vec<f32>,
+}
+
+impl ContinuedFraction {
+    /// Gets the spectral function on a grid of
+    /// frequencies, where the peaks are Lorentzians of
+    /// half-width `broadening`.
+    pub fn get_spectral_function(
+        &self,
+        frequencies: &[f32],
+        broadening: f32,
+    ) -> SpectralFunction {
+        let intensities = frequencies
+            .iter()
+            .map(|frequency| {
+                let z = Complex64::new((frequency + self.ground_state_energy) as f64, broadening as f64);
+                (-(self.weight as f64) * self.tridiagonal.get_resolvent(z).im / PI) as f32
+            })
+            .collect();
+
+        SpectralFunction {
+            frequencies: frequencies.to_vec(),
+            intensities,
+        }
+    }
+}
+
+/// Gets the continued fraction of the dynamical structure
+/// factor of a ground state of a [`Sector`], given with
+/// its energy, for a spin operator with momentum q.
+/// A_q|0⟩ is projected onto `target_sector`, which must
+/// have the momentum and the total quantum spin in the
+/// z-direction reached by A_q, so only the part of
+/// S(q, ω) from this sector is calculated. If it uses
+/// translation, q must be 0 or π. The Lanczos recursion
+/// runs for at most `lanczos_steps` steps.
+pub fn get_dynamical_structure_factor<T: Implemented>(
+    hamiltonian: &T,
+    ground_state: &(State, f32),
+    ground_state_sector: &Sector,
+    target_sector: &Sector,
+    operator: SpinOperator,
+    momentum: f32,
+    lanczos_steps: usize,
+) -> Result<ContinuedFraction, Error> {
+    let (ground_state, ground_state_energy) = ground_state;
+    let model = ground_state_sector.model;
+    let target_model = target_sector.model;
+
+    if target_model.base != model.base || target_model.length != model.length {
+        return Err(Error::IncompatibleSectors);
+    }
+
+    if target_model.symmetries.translation && !is_real_momentum(momentum) {
+        return Err(Error::NonRealMomentum(momentum));
+    }
+
+    let norm_squared = ground_state.dot(ground_state, ground_state_sector)?;
+
+    if norm_squared == 0.0 || !norm_squared.is_finite() {
+        return Err(Error::VanishingNorm);
+    }
+
+    let full_state = ground_state.get_full_state(ground_state_sector)?;
+    let (full_real, full_imaginary) = apply_spin_operator(
+        &full_state,
+        model.base,
+        model.length,
+        operator,
+        momentum,
+    );
+    let scaler = 1.0 / norm_squared.sqrt();
+    let excited_state = ComplexState {
+        real: &project(target_sector, &full_real)? * scaler,
+        imaginary: &project(target_sector, &full_imaginary)? * scaler,
+    };
+    let weight = excited_state.dot(&excited_state, target_sector)?.re;
+
+    if weight == 0.0 || !weight.is_finite() {
+        return Err(Error::VanishingNorm);
+    }
+
+    Ok(ContinuedFraction {
+        tridiagonal: get_lanczos_coefficients(hamiltonian, &excited_state, target_sector, lanczos_steps)?,
+        ground_state_energy: *ground_state_energy,
+        weight,
+    })
+}
+
+/// Checks whether a momentum is 0 or π modulo 2π.
+fn is_real_momentum(momentum: f32) -> bool {
+    let reduced_momentum = momentum.rem_euclid(2.0 * PI as f32);
+
+    [0.0, PI, 2.0 * PI]
+        .iter()
+        .any(|real_momentum| (reduced_momentum as f64 - real_momentum).abs() < 1e-4)
+}
+
+/// Applies A_q = L^{-1/2} Σ_j e^{iqj} S^a_j to a vector in
+/// the full basis, which gives the real and imaginary
+/// parts of the result.
+fn apply_spin_operator(
+    full_state: &[f32],
+    base: u8,
+    length: u8,
+    operator: SpinOperator,
+    momentum: f32,
+) -> (Vec<f32>, Vec<f32>) {
+    let spin = (base - 1) as f32 / 2.0;
+    let scaler = 1.0 / (length as f32).sqrt();
+    let mut full_real = vec![0.0; full_state.len()];
+    let mut full_imaginary = vec![0.0; full_state.len()];
+
+    for (value, coefficient) in full_state.iter().enumerate() {
+        if *coefficient == 0.0 {
+            continue;
+        }
+
+        let mut remaining_value = value;
+        let mut base_power = 1;
+
+        for site in 0..length {
+            let digit = remaining_value % base as usize;
+            let m = digit as f32 - spin;
+            let (sine, cosine) = (momentum * site as f32).sin_cos();
+            let (new_value, matrix_element) = match operator {
+                SpinOperator::Z => (value, m),
+                SpinOperator::Plus if digit + 1 < base as usize => {
+                    (value + base_power, (spin * (spin + 1.0) - m * (m + 1.0)).sqrt())
+                },
+                SpinOperator::Plus => (value, 0.0),
+            };
+
+            full_real[new_value] += scaler * cosine * matrix_element * coefficient;
+            full_imaginary[new_value] += scaler * sine * matrix_element * coefficient;
+            remaining_value /= base as usize;
+            base_power *= base as usize;
+        }
+    }
+
+    (full_real, full_imaginary)
+}
+
+/// Projects a vector in the full basis onto a
+/// [`Sector`], where a vanishing projection gives the
+/// zero [`State`].
+fn project(sector: &Sector, full_state: &[f32]) -> Result<State, Error> {
+    match sector.from_full_state(full_state) {
+        Err(Error::VanishingNorm) => Ok(sector.from_zeros()),
+        result => result,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::basis::SzSector;
+    use crate::hamiltonians::{Hamiltonian, AFH};
+    use crate::model::Model;
+    use crate::symmetries::{Momentum, Parity};
+    use crate::testing::{get_eigenpairs, get_sector_matrix};
+
+    /// Applies S^z_q = L^{-1/2} Σ_j cos(qj) S^z_j to the full
+    /// vector of a [`State`] for a real momentum q, and
+    /// divides by the norm of the [`State`].
+    fn apply_s_z(full_state: &[f32], base: u8, length: u8, momentum: f32) -> Vec<f32> {
+        let spin = (base - 1) as f32 / 2.0;
+        let norm = full_state.iter().map(|coefficient| coefficient * coefficient).sum::<f32>().sqrt();
+
+        full_state
+            .iter()
+            .enumerate()
+            .map(|(value, coefficient)| {
+                let s_z_q: f32 = (0..length as u32)
+                    .map(|site| {
+                        let digit = (value / (base as usize).pow(site)) % base as usize;
+                        (momentum * site as f32).cos() * (digit as f32 - spin)
+                    })
+                    .sum();
+
+                s_z_q * coefficient / (length as f32).sqrt() / norm
+            })
+            .collect()
+    }
+
+    /// Gets the coefficients of a [`State`] in the
+    /// orthonormal basis of [`get_sector_matrix`].
+    fn get_orthonormal_coefficients(state: &State, sector: &Sector) -> Vec<f64> {
+        let symmetry_factors = &sector.symmetry_factors;
+
+        state.coefficients
+            .iter()
+            .zip(symmetry_factors.factors.iter())
+            .filter(|(_, factor)| **factor != 0.0)
+            .map(|(coefficient, factor)| *coefficient as f64 * (symmetry_factors.group_order as f64 / *factor as f64).sqrt())
+            .collect()
+    }
+
+    /// Gets the sum of the weights of the poles within
+    /// 1e-3 of an energy.
+    fn get_weight_near(poles: &[(f64, f64)], energy: f64) -> f64 {
+        poles
+            .iter()
+            .filter(|(pole, _)| (pole - energy).abs() < 1e-3)
+            .map(|(_, weight)| weight)
+            .sum()
+    }
+
+    #[test]
+    fn weights_and_poles_agree_with_brute_force() {
+        let hamiltonian = Hamiltonian::<AFH>::new(1.0);
+        let model = Model::new(3, 6, SzSector::new(0)).unwrap();
+        let ground_state_sector = Sector::trivial(&model).unwrap();
+        let ground_state = ground_state_sector.find_eigenstate(&hamiltonian, 1000, &[]).unwrap();
+        let full_state = ground_state.0.get_full_state(&ground_state_sector).unwrap();
+        let excited_state = apply_s_z(&full_state, 3, 6, PI as f32);
+        // The sum rule: the weights of all the sectors add
+        // up to ⟨0|A_q^† A_q|0⟩.
+        let expected_total_weight: f32 = excited_state.iter().map(|coefficient| coefficient * coefficient).sum();
+        let mut total_weight = 0.0;
+
+        for target_sector in Sector::get_all(&model).unwrap() {
+            if target_sector.translation != Momentum::Pi {
+                continue;
+            }
+
+            let continued_fraction = match get_dynamical_structure_factor(
+                &hamiltonian,
+                &ground_state,
+                &ground_state_sector,
+                &target_sector,
+                SpinOperator::Z,
+                PI as f32,
+                60,
+            ) {
+                Ok(continued_fraction) => continued_fraction,
+                Err(Error::VanishingNorm) => continue,
+                Err(error) => panic!("{}", error),
+            };
+            total_weight += continued_fraction.weight;
+
+            // The poles of the continued fraction, weighted by
+            // the squared first components of the eigenvectors.
+            let (eigenvalues, eigenvectors) = continued_fraction.tridiagonal.get_eigenpairs().unwrap();
+            let poles: Vec<(f64, f64)> = eigenvalues
+                .iter()
+                .zip(eigenvectors.iter())
+                .map(|(eigenvalue, eigenvector)| (*eigenvalue, continued_fraction.weight as f64 * eigenvector[0] * eigenvector[0]))
+                .collect();
+            let coefficients = get_orthonormal_coefficients(&project(&target_sector, &excited_state).unwrap(), &target_sector);
+            let expected_poles: Vec<(f64, f64)> = get_eigenpairs(&get_sector_matrix(&hamiltonian, &target_sector))
+                .into_iter()
+                .map(|(eigenvalue, eigenvector)| {
+                    let overlap: f64 = eigenvector.iter().zip(coefficients.iter()).map(|(a, b)| a * b).sum();
+
+                    (eigenvalue, overlap * overlap)
+                })
+                .collect();
+
+            for (energy, _) in expected_poles.iter().chain(poles.iter()) {
+                let weight = get_weight_near(&poles, *energy);
+                let expected_weight = get_weight_near(&expected_poles, *energy);
+
+                assert!((weight - expected_weight).abs() < 1e-4, "E {}: {} != {}", energy, weight, expected_weight);
+            }
+        }
+
+        assert!(total_weight > 0.1);
+        assert!((total_weight - expected_total_weight).abs() < 1e-4 * expected_total_weight);
+    }
+
+    #[test]
+    fn non_real_momenta_are_rejected_with_translation() {
+        let hamiltonian = Hamiltonian::<AFH>::new(1.0);
+        let model = Model::new(3, 6, SzSector::new(0)).unwrap();
+        let ground_state_sector = Sector::trivial(&model).unwrap();
+        let target_sector = Sector::new(&model, Parity::Odd, Parity::Odd, Momentum::Pi).unwrap();
+        let ground_state = (ground_state_sector.from_random(0), 0.0);
+        let get_continued_fraction = |momentum| get_dynamical_structure_factor(
+            &hamiltonian,
+            &ground_state,
+            &ground_state_sector,
+            &target_sector,
+            SpinOperator::Z,
+            momentum,
+            5,
+        );
+
+        assert_eq!(get_continued_fraction(PI as f32 / 3.0), Err(Error::NonRealMomentum(PI as f32 / 3.0)));
+        assert!(get_continued_fraction(-PI as f32).is_ok());
+        assert!(get_continued_fraction(3.0 * PI as f32).is_ok());
+    }
+}
\ No newline at end of file
@@ -0,0 +1,236 @@
+//! This module contains the [`sweep`] function, which
+//! walks a path of Hamiltonians given by a parameter,
+//! such as the interpolation parameter s of
+//! [`crate::hamiltonians::AFH`] and
+//! [`crate::hamiltonians::AKLT`]. Every power iteration
+//! is started from the eigenstate of the previous
+//! parameter, so far fewer iterations are needed than
+//! when starting from random states.
+use crate::error::Error;
+use crate::hamiltonians::Implemented;
+use crate::sector::Sector;
+use crate::states::State;
+
+/// An observable calculated from the ground state and
+/// the [`Sector`] it belongs to.
+pub type Observable<'a> = &'a dyn Fn(&State, &Sector) -> Result<f32, Error>;
+
+/// The results of a [`sweep`] at one parameter.
+#[derive(Clone, PartialEq, Debug)]
+pub struct SweepPoint {
+    pub parameter: f32,
+    /// The lowest eigenvalues of every sector in
+    /// increasing order.
+    pub energies: Vec<Vec<f32>>,
+    /// The lowest eigenvalue of all the sectors.
+    pub ground_state_energy: f32,
+    /// The difference between the two lowest eigenvalues
+    /// of all the sectors, if more than one is calculated.
+    pub gap: Option<f32>,
+    /// The observables of the ground state.
+    pub observables: Vec<f32>,
+}
+
+/// Finds the `levels` lowest eigenpairs in every sector
+/// for every parameter, where `get_hamiltonian` creates
+/// the Hamiltonian of a parameter. The power iteration
+/// for a parameter is started from the eigenstate of the
+/// previous parameter, so `iterations` is the number of
+/// iterations used for every parameter but the first,
+/// which uses `initial_iterations`.
+pub fn sweep<T, H>(
+    sectors: &[Sector],
+    parameters: &[f32],
+    get_hamiltonian: H,
+    levels: usize,
+    initial_iterations: u32,
+    iterations: u32,
+    observables: &[Observable],
+) -> Result<Vec<SweepPoint>, Error> where T: Implemented, H: Fn(f32) -> T {
+    let mut sweep_points = Vec::with_capacity(parameters.len());
+
+    sweep_ground_states(
+        sectors,
+        parameters,
+        get_hamiltonian,
+        levels,
+        initial_iterations,
+        iterations,
+        |parameter, energies, ground_state_sector_index, ground_state| {
+            let ground_state_energy = energies[ground_state_sector_index][0];
+            let mut all_energies: Vec<f32> = energies.iter().flatten().copied().collect();
+            all_energies.sort_by(|energy_0, energy_1| energy_0.total_cmp(energy_1));
+            let gap = all_energies.get(1).map(|energy| energy - ground_state_energy);
+            let ground_state_sector = &sectors[ground_state_sector_index];
+            let observables = observables
+                .iter()
+                .map(|observable| observable(ground_state, ground_state_sector))
+                .collect::<Result<Vec<f32>, Error>>()?;
+
+            sweep_points.push(SweepPoint {
+                parameter,
+                energies,
+                ground_state_energy,
+                gap,
+                observables,
+            });
+
+            Ok(())
+        },
+    )?;
+
+    Ok(sweep_points)
+}
+
+/// Finds the eigenpairs in the same way as [`sweep`] and
+/// calls `visit` for every parameter with the lowest
+/// eigenvalues of every sector, the index of the sector
+/// of the ground state and the ground state.
+pub(crate) fn sweep_ground_states<T, H, F>(
+    sectors: &[Sector],
+    parameters: &[f32],
+    get_hamiltonian: H,
+    levels: usize,
+    initial_iterations: u32,
+    iterations: u32,
+    mut visit: F,
+) -> Result<(), Error> where T: Implemented, H: Fn(f32) -> T, F: FnMut(f32, Vec<Vec<f32>>, usize, &State) -> Result<(), Error> {
+    let mut previous_eigenpairs: Vec<Vec<(State, f32)>> = sectors.iter().map(|_| Vec::new()).collect();
+
+    for (parameter_index, parameter) in parameters.iter().enumerate() {
+        let hamiltonian = get_hamiltonian(*parameter);
+        let iterations = if parameter_index == 0 {initial_iterations} else {iterations};
+        let mut energies = Vec::with_capacity(sectors.len());
+        let mut ground_state: Option<(usize, f32)> = None;
+
+        for (sector_index, sector) in sectors.iter().enumerate() {
+            let mut previous_states = std::mem::take(&mut previous_eigenpairs[sector_index])
+                .into_iter()
+                .map(|(state, _)| state);
+            let mut eigenpairs: Vec<(State, f32)> = Vec::with_capacity(levels);
+
+            for level in 0..levels {
+                let seed = sector.model.seed.wrapping_add(level as u64);
+                let initial_state = previous_states.next().unwrap_or_else(|| sector.from_random(seed));
+                let eigenpair = sector.find_eigenstate_from(&hamiltonian, iterations, initial_state, &eigenpairs)?;
+                eigenpairs.push(eigenpair);
+            }
+
+            eigenpairs.sort_by(|eigenpair_0, eigenpair_1| eigenpair_0.1.total_cmp(&eigenpair_1.1));
+
+            if let Some((_, energy)) = eigenpairs.first() {
+                if ground_state.map_or(true, |(_, ground_state_energy)| *energy < ground_state_energy) {
+                    ground_state = Some((sector_index, *energy));
+                }
+            }
+
+            energies.push(eigenpairs.iter().map(|(_, energy)| *energy).collect::<Vec<f32>>());
+            previous_eigenpairs[sector_index] = eigenpairs;
+        }
+
+        let Some((ground_state_sector_index, _)) = ground_state else {
+            return Err(Error::EmptySector);
+        };
+        let ground_state = &previous_eigenpairs[ground_state_sector_index][0].0;
+        visit(*parameter, energies, ground_state_sector_index, ground_state)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::basis::SzSector;
+    use crate::hamiltonians::{Hamiltonian, AFH};
+    use crate::model::Model;
+    use crate::symmetries::Reflection;
+    use crate::testing::get_brute_force_spectrum;
+
+    const PARAMETERS: [f32; 3] = [0.6, 0.8, 1.0];
+
+    /// Gets the expectation value of Σ (S^z_n)^2 from the
+    /// full vector of a [`State`].
+    fn get_anisotropy(state: &State, sector: &Sector) -> Result<f32, Error> {
+        let model = sector.model;
+        let full_state = state.get_full_state(sector)?;
+        let mut anisotropy = 0.0;
+
+        for (value, coefficient) in full_state.iter().enumerate() {
+            let s_z_squared: f32 = (0..model.length as u32)
+                .map(|site| ((value / (model.base as usize).pow(site)) % model.base as usize) as f32 - model.spin)
+                .map(|s_z| s_z * s_z)
+                .sum();
+            anisotropy += coefficient * coefficient * s_z_squared;
+        }
+
+        Ok(anisotropy / full_state.iter().map(|coefficient| coefficient * coefficient).sum::<f32>())
+    }
+
+    #[test]
+    fn warm_starts_agree_with_cold_starts() {
+        let model = Model::new(3, 6, SzSector::new(0)).unwrap();
+        // Two levels are only found in sectors with at least
+        // two basis states.
+        let sectors: Vec<Sector> = Sector::get_all(&model)
+            .unwrap()
+            .into_iter()
+            .filter(|sector| sector.symmetry_factors.factors.iter().filter(|factor| **factor != 0.0).count() >= 2)
+            .collect();
+        let sweep_points = sweep(&sectors, &PARAMETERS, Hamiltonian::<AFH>::new, 2, 1000, 300, &[]).unwrap();
+
+        for sweep_point in sweep_points.iter() {
+            let hamiltonian = Hamiltonian::<AFH>::new(sweep_point.parameter);
+
+            for (sector, energies) in sectors.iter().zip(sweep_point.energies.iter()) {
+                let mut eigenpairs = Vec::new();
+
+                for _ in 0..energies.len() {
+                    let eigenpair = sector.find_eigenstate(&hamiltonian, 1000, &eigenpairs).unwrap();
+                    eigenpairs.push(eigenpair);
+                }
+
+                let mut expected: Vec<f32> = eigenpairs.iter().map(|(_, energy)| *energy).collect();
+                expected.sort_by(f32::total_cmp);
+
+                for (energy, expected) in energies.iter().zip(expected.iter()) {
+                    assert!((energy - expected).abs() < 1e-3, "s {}: {} != {}", sweep_point.parameter, energy, expected);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn gaps_and_observables_agree_with_brute_force() {
+        let model = Model::new(3, 6, SzSector::new(0)).unwrap();
+        let sectors = Sector::get_all(&model).unwrap();
+        let sweep_points = sweep(&sectors, &PARAMETERS, Hamiltonian::<AFH>::new, 1, 1000, 300, &[&get_anisotropy]).unwrap();
+        let get_spectrum = |s: f32| get_brute_force_spectrum(&Hamiltonian::<AFH>::new(s), 3, 6, SzSector::new(0), (None, None, None));
+        let step = 0.01;
+
+        for sweep_point in sweep_points.iter() {
+            let s = sweep_point.parameter;
+            let spectrum = get_spectrum(s);
+            let mut sector_ground_state_energies: Vec<f64> = sectors
+                .iter()
+                .map(|sector| get_brute_force_spectrum(
+                    &Hamiltonian::<AFH>::new(s),
+                    3,
+                    6,
+                    SzSector::new(0),
+                    (Some(sector.time_reversal), Some((Reflection::Bond, sector.reflection)), Some(sector.translation)),
+                )[0])
+                .collect();
+            sector_ground_state_energies.sort_by(f64::total_cmp);
+            // By the Hellmann-Feynman theorem the derivative of
+            // E_0 = s<H_AFH> + (1-s)<Σ (S^z_n)^2> is
+            // <H_AFH> - <Σ (S^z_n)^2>.
+            let derivative = (get_spectrum(s + step)[0] - get_spectrum(s - step)[0]) / (2.0 * step as f64);
+            let anisotropy = spectrum[0] - s as f64 * derivative;
+
+            assert!((sweep_point.ground_state_energy as f64 - spectrum[0]).abs() < 1e-3);
+            assert!((sweep_point.gap.unwrap() as f64 - (sector_ground_state_energies[1] - spectrum[0])).abs() < 1e-3);
+            assert!((sweep_point.observables[0] as f64 - anisotropy).abs() < 1e-2, "s {}: {} != {}", s, sweep_point.observables[0], anisotropy);
+        }
+    }
+}
\ No newline at end of file
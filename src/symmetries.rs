@@ -1,137 +1,562 @@
-//! This module contains the functions used to handle 
-//! time-reversal symmetry, reflecion symmetry and 
+//! This module contains the functions used to handle
+//! time-reversal symmetry, reflecion symmetry and
 //! translation symmetry.
-/// Calculates the symmetry factors.
+use crate::basis::{check_chain, BasisStates, SzSector};
+use crate::error::Error;
+use crate::spin_half;
+
+/// The center of a reflection.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Reflection {
+    /// Maps site n to site length-1-n, which is centered
+    /// on a bond of a periodic chain.
+    Bond,
+    /// Maps site n to site length-n (modulo length),
+    /// which is centered on site 0. It requires a
+    /// periodic chain.
+    Site,
+}
+
+/// An eigenvalue of time reversal or reflection.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Parity {
+    /// The eigenvalue 1.
+    Even,
+    /// The eigenvalue -1.
+    Odd,
+}
+
+/// An eigenvalue of translation by one site.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Momentum {
+    /// The eigenvalue 1.
+    Zero,
+    /// The eigenvalue -1, which requires an even length.
+    Pi,
+}
+
+/// The symmetries used to symmetrize the basis states.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Symmetries {
+    /// Whether the total quantum spin in the
+    /// z-direction is conserved.
+    pub s_z_conservation: bool,
+    /// Whether time reversal (spin flip) is used.
+    pub time_reversal: bool,
+    /// The reflection used, if any.
+    pub reflection: Option<Reflection>,
+    /// Whether translation is used.
+    pub translation: bool,
+}
+
+impl Symmetries {
+    /// Creates a [`Symmetries`] without any symmetries.
+    pub fn none() -> Self {
+        Self {
+            s_z_conservation: false,
+            time_reversal: false,
+            reflection: None,
+            translation: false,
+        }
+    }
+
+    /// Creates a [`Symmetries`] with S^z conservation,
+    /// time reversal, bond-centered reflection and
+    /// translation.
+    pub fn all() -> Self {
+        Self {
+            s_z_conservation: true,
+            time_reversal: true,
+            reflection: Some(Reflection::Bond),
+            translation: true,
+        }
+    }
+
+    /// Gets the symmetries that leave a sector of the 
+    /// total quantum spin in the z-direction invariant. 
+    /// Time reversal maps S^z to -S^z, so it is disabled 
+    /// if S^z is conserved and nonzero.
+    pub fn restrict(&self, total_s_z: Option<SzSector>) -> Self {
+        let time_reversible = match total_s_z {
+            Some(total_s_z) => total_s_z.twice_s_z == 0,
+            None => true,
+        };
+
+        Self {
+            s_z_conservation: total_s_z.is_some(),
+            time_reversal: self.time_reversal && time_reversible,
+            ..*self
+        }
+    }
+
+    /// Gets the number of elements of the group
+    /// generated by time reversal, reflection and
+    /// translation.
+    pub fn get_group_order(&self, length: u8) -> usize {
+        let mut group_order = 1;
+
+        if self.time_reversal {
+            group_order *= 2;
+        }
+
+        if self.reflection.is_some() {
+            group_order *= 2;
+        }
+
+        if self.translation {
+            group_order *= length as usize;
+        }
+
+        group_order
+    }
+}
+
+/// The symmetry factors of the representers of
+/// [`BasisStates`] together with the symmetry
+/// eigenvalues they were calculated from.
+pub struct SymmetryFactors {
+    /// The time-reversal, reflection and translation
+    /// eigenvalues (`true` corresponds to -1).
+    pub eigenvalues: [bool; 3],
+    /// The symmetry factors of the representers.
+    pub factors: Vec<f32>,
+    /// Number of elements of the symmetry group.
+    pub group_order: usize,
+}
+
+impl SymmetryFactors {
+    /// Gets the character of a symmetry, that is the
+    /// product of the eigenvalues given by its symmetry sign.
+    pub fn get_sign(&self, symmetry_sign: [bool; 3]) -> f32 {
+        let total_sign = symmetry_sign[0]&self.eigenvalues[0]
+            ^ symmetry_sign[1]&self.eigenvalues[1]
+            ^ symmetry_sign[2]&self.eigenvalues[2];
+
+        if total_sign {-1.0} else {1.0}
+    }
+}
+
+/// Calculates the symmetry factors. The eigenvalues of
+/// symmetries that are not used by the basis states
+/// have no effect. The momentum π requires an even
+/// length, which is checked by [`crate::sector::Sector::new`].
 pub fn get_symmetry_factors(
-    symmetry_signs: &Vec<Vec<[bool; 3]>>,
-    time_reversal_eigenvalue: bool,
-    reflection_eigenvalue: bool,
-    translation_eigenvalue: bool,
-) -> Vec<f32> {
-    let mut symmetry_factors = Vec::with_capacity(symmetry_signs.len());
-
-    for symmetry_signs_ in symmetry_signs {
-        let mut symmetry_factor = 0.0;
+    basis_states: &BasisStates,
+    time_reversal_eigenvalue: Parity,
+    reflection_eigenvalue: Parity,
+    translation_eigenvalue: Momentum,
+) -> SymmetryFactors {
+    let mut symmetry_factors = SymmetryFactors {
+        eigenvalues: [
+            time_reversal_eigenvalue == Parity::Odd,
+            reflection_eigenvalue == Parity::Odd,
+            translation_eigenvalue == Momentum::Pi,
+        ],
+        factors: Vec::with_capacity(basis_states.length),
+        group_order: basis_states.group_order,
+    };
 
-        for symmetry_sign in symmetry_signs_ {
-            let total_sign = symmetry_sign[0]&time_reversal_eigenvalue
-                ^ symmetry_sign[1]&reflection_eigenvalue
-                ^ symmetry_sign[2]&translation_eigenvalue;
+    for symmetry_sign_counts in basis_states.symmetry_signs.iter() {
+        let mut symmetry_factor = 0.0;
 
-            symmetry_factor += if total_sign {-1.0} else {1.0}
+        for (sign_index, count) in symmetry_sign_counts.iter().enumerate() {
+            let symmetry_sign = get_symmetry_sign(sign_index);
+            symmetry_factor += symmetry_factors.get_sign(symmetry_sign) * *count as f32;
         }
 
-        symmetry_factors.push(symmetry_factor)
+        symmetry_factors.factors.push(symmetry_factor)
     }
 
     symmetry_factors
 }
 
-/// Finds all the basis states that are equivalent 
-/// under the three symmetries, and gets the 
-/// corresponding symmetry signs.
+/// Counts the symmetries that leave a basis state
+/// invariant, arranged by their symmetry signs.
+pub fn get_symmetry_sign_counts(
+    value: usize,
+    base: u8,
+    length: u8,
+    symmetries: &Symmetries,
+) -> [u8; 8] {
+    let mut symmetry_sign_counts = [0; 8];
+
+    for_each_equivalent(value, base, length, symmetries, |other_value, symmetry_sign| {
+        if other_value == value {
+            symmetry_sign_counts[get_sign_index(symmetry_sign)] += 1;
+        }
+
+        true
+    });
+
+    symmetry_sign_counts
+}
+
+/// Finds the representer of the equivalence class of a
+/// basis state, which is the smallest equivalent basis
+/// state, and the symmetry sign of a symmetry mapping
+/// the basis state to it.
+pub fn get_representer(
+    value: usize,
+    base: u8,
+    length: u8,
+    symmetries: &Symmetries,
+) -> (usize, [bool; 3]) {
+    let mut representer = (value, [false, false, false]);
+
+    for_each_equivalent(value, base, length, symmetries, |other_value, symmetry_sign| {
+        if other_value < representer.0 {
+            representer = (other_value, symmetry_sign)
+        }
+
+        true
+    });
+
+    representer
+}
+
+/// Checks whether a basis state is the representer of
+/// its equivalence class.
+pub fn is_representer(
+    value: usize,
+    base: u8,
+    length: u8,
+    symmetries: &Symmetries,
+) -> bool {
+    let mut is_representer = true;
+
+    for_each_equivalent(value, base, length, symmetries, |other_value, _| {
+        is_representer = other_value >= value;
+        is_representer
+    });
+
+    is_representer
+}
+
+/// Finds all the basis states that are equivalent
+/// under the symmetries, and gets the corresponding
+/// symmetry signs.
 pub fn get_eq_class(
-    digits: Vec<u8>,
+    value: usize,
     base: u8,
     length: u8,
-    base_powers: &Vec<usize>,
+    symmetries: &Symmetries,
 ) -> Vec<(usize, [bool; 3])> {
-    let all_time_reversed_reflected_digits = time_reverse_reflect_digits(
-        digits,
-        base,
-    );
+    let mut eq_class = Vec::with_capacity(symmetries.get_group_order(length));
 
-    let mut eq_class = Vec::new();
+    for_each_equivalent(value, base, length, symmetries, |other_value, symmetry_sign| {
+        eq_class.push((other_value, symmetry_sign));
+        true
+    });
 
-    for (time_reversed_reflected_digits, sign) in all_time_reversed_reflected_digits {
-        let mut translations = get_translations(
-            time_reversed_reflected_digits,
-            length,
-            base_powers,
-            sign,
-        );
+    eq_class
+}
 
-        eq_class.append(&mut translations)
+/// Visits all the basis states that are equivalent
+/// under the symmetries, until `function` returns
+/// `false`. The symmetries must leave the sector
+/// invariant, see [`Symmetries::restrict`].
+fn for_each_equivalent<F>(
+    value: usize,
+    base: u8,
+    length: u8,
+    symmetries: &Symmetries,
+    mut function: F,
+) where F: FnMut(usize, [bool; 3]) -> bool {
+    if base == 2 {
+        return spin_half::for_each_equivalent(value, length, symmetries, function);
     }
 
-    eq_class
+    let mut digits = [0; usize::BITS as usize];
+    let digits = &mut digits[..length as usize];
+    let mut remaining_value = value;
+
+    for digit in digits.iter_mut() {
+        *digit = (remaining_value % base as usize) as u8;
+        remaining_value /= base as usize;
+    }
+
+    let mut reflected_digits = [0; usize::BITS as usize];
+    let reflected_digits = &mut reflected_digits[..length as usize];
+
+    for (index, reflected_digit) in reflected_digits.iter_mut().enumerate() {
+        *reflected_digit = match symmetries.reflection {
+            Some(Reflection::Site) => digits[(length as usize - index) % length as usize],
+            _ => digits[length as usize - 1 - index],
+        };
+    }
+
+    let max_value = (base as usize).pow(length as u32);
+    let reflected_value = reflected_digits
+        .iter()
+        .rev()
+        .fold(0, |reflected_value, digit| reflected_value * base as usize + *digit as usize);
+    let translations = if symmetries.translation {length as usize} else {1};
+    let time_reversed_reflected_values = [
+        (value, &*digits, [false, false, false]),
+        (max_value - 1 - value, &*digits, [true, false, false]),
+        (reflected_value, &*reflected_digits, [false, true, false]),
+        (max_value - 1 - reflected_value, &*reflected_digits, [true, true, false]),
+    ];
+
+    for (time_reversed_reflected_value, digits, mut symmetry_sign) in time_reversed_reflected_values {
+        if symmetry_sign[0] && !symmetries.time_reversal {
+            continue;
+        }
+
+        if symmetry_sign[1] && symmetries.reflection.is_none() {
+            continue;
+        }
+
+        let mut translation = time_reversed_reflected_value;
+
+        // The translations are generated by moving the
+        // last digit to the front, which is a translation
+        // by one site.
+        for translate in 0..translations {
+            symmetry_sign[2] = get_translation_sign(translate);
+            if !function(translation, symmetry_sign) {
+                return;
+            }
+
+            let mut last_digit = digits[length as usize - 1 - translate];
+
+            if symmetry_sign[0] {
+                last_digit = base - 1 - last_digit;
+            }
+
+            translation = translation
+                .wrapping_mul(base as usize)
+                .wrapping_sub(last_digit as usize * (max_value - 1));
+        }
+    }
+}
+
+/// Gets the symmetry sign of a translation by
+/// `translate` sites, which is negative if the momentum
+/// π gives it the character -1. This is a character of
+/// the translations only for an even length, so the
+/// momentum π is rejected for odd lengths.
+pub fn get_translation_sign(translate: usize) -> bool {
+    translate % 2 != 0
+}
+
+/// Gets the symmetry sign corresponding to an index
+/// of the symmetry sign counts.
+fn get_symmetry_sign(sign_index: usize) -> [bool; 3] {
+    [sign_index & 1 != 0, sign_index & 2 != 0, sign_index & 4 != 0]
+}
+
+/// Gets the index of the symmetry sign counts
+/// corresponding to a symmetry sign.
+fn get_sign_index(symmetry_sign: [bool; 3]) -> usize {
+    symmetry_sign[0] as usize | (symmetry_sign[1] as usize) << 1 | (symmetry_sign[2] as usize) << 2
 }
 
-/// Time-reverses and reflects a basis state.
-fn time_reverse_reflect_digits(
-    digits: Vec<u8>,
+/// Applies time reversal (spin flip) to a basis state,
+/// which maps every digit d to base-1-d.
+pub fn time_reverse(
+    value: usize,
     base: u8,
-) -> [(Vec<u8>, [bool; 3]); 4] {
-    let [digits, time_reversed_digits] = time_reverse_digits(digits, base);
-    let [digits, reflected_digits] = reflect_digits(digits);
-    let [reflected_digits, time_reversed_reflected_digits] = time_reverse_digits(reflected_digits, base);
-
-    [
-        (digits, [false, false, false]),
-        (time_reversed_digits, [true, false, false]),
-        (reflected_digits, [false, true, false]),
-        (time_reversed_reflected_digits, [true, true, false]),
-    ]
-}
-
-/// Time-reverses a basis state.
-fn time_reverse_digits(
-    digits: Vec<u8>,
+    length: u8,
+) -> usize {
+    (base as usize).pow(length as u32) - 1 - value
+}
+
+/// Applies a reflection to a basis state.
+pub fn reflect(
+    value: usize,
     base: u8,
-) -> [Vec<u8>; 2] {
-    let time_reversed_digits = digits
-        .iter()
-        .map(
-            |digit| base - 1 - *digit
-        )
-        .collect();
+    length: u8,
+    reflection: Reflection,
+) -> Result<usize, Error> {
+    check_chain(base, length, None)?;
+
+    let mut remaining_value = value;
+    let mut reflected_value = 0;
+
+    for _ in 0..length {
+        reflected_value = reflected_value * base as usize + remaining_value % base as usize;
+        remaining_value /= base as usize;
+    }
 
-    [digits, time_reversed_digits]
+    match reflection {
+        Reflection::Bond => Ok(reflected_value),
+        Reflection::Site => translate(reflected_value, base, length),
+    }
 }
 
-/// Reflects a basis state.
-fn reflect_digits(
-    digits: Vec<u8>,
-) -> [Vec<u8>; 2] {
-    let mut revflected_digits = digits.clone();
-    revflected_digits.reverse();
+/// Translates a basis state by one site, which moves 
+/// the digit of site n to site n+1 (modulo length).
+pub fn translate(
+    value: usize,
+    base: u8,
+    length: u8,
+) -> Result<usize, Error> {
+    check_chain(base, length, None)?;
+
+    let last_power = (base as usize).pow(length as u32 - 1);
 
-    [digits, revflected_digits]
+    Ok((value % last_power) * base as usize + value / last_power)
 }
 
-/// Finds the translations of a basis state.
-fn get_translations(
-    digits: Vec<u8>,
+/// Gets the phase e^{iπSL}, which relates the π-rotation 
+/// exp(iπS^x_tot) to time reversal, that is 
+/// exp(iπS^x_tot)|v> = e^{iπSL}|base^length-1-v>. It is 
+/// real if and only if S*length is an integer, and `None` 
+/// is returned otherwise.
+pub fn get_pi_rotation_phase(
+    base: u8,
     length: u8,
-    base_powers: &Vec<usize>,
-    mut symmetry_sign: [bool; 3],
-) -> Vec<(usize, [bool; 3])> {
-    let basis_state = get_translation(&digits, 0, length, base_powers);
-    let mut translations = vec![(basis_state, symmetry_sign)];
-    let mut translation;
-    
-    for translate in 1..length {
-        symmetry_sign[2] = !symmetry_sign[2];
-        translation = get_translation(&digits, translate, length, base_powers);
-        translations.push((translation, symmetry_sign));
+) -> Option<f32> {
+    let twice_spin_length = (base as usize - 1) * length as usize;
+
+    if twice_spin_length % 2 != 0 {
+        return None;
     }
 
-    translations
+    Some(if twice_spin_length / 2 % 2 == 0 {1.0} else {-1.0})
 }
 
-/// Translates a basis state.
-fn get_translation(
-    digits: &Vec<u8>,
-    translate: u8,
+/// Applies the π-rotation exp(iπS^x_tot) to a basis 
+/// state, giving the rotated basis state and the phase. 
+/// `None` is returned if the phase is not real.
+pub fn rotate_x(
+    value: usize,
+    base: u8,
+    length: u8,
+) -> Option<(usize, f32)> {
+    let phase = get_pi_rotation_phase(base, length)?;
+
+    Some((time_reverse(value, base, length), phase))
+}
+
+/// Gets the eigenvalue e^{iπS^z_tot} of the π-rotation 
+/// exp(iπS^z_tot) of a basis state. `None` is returned 
+/// if it is not real.
+pub fn rotate_z(
+    value: usize,
+    base: u8,
+    length: u8,
+) -> Option<f32> {
+    let phase = get_pi_rotation_phase(base, length)?;
+    let digit_sum = get_digit_sum(value, base, length);
+
+    Some(if digit_sum % 2 == 0 {phase} else {-phase})
+}
+
+/// Calculates the sum of the digits of a basis state.
+pub fn get_digit_sum(
+    value: usize,
+    base: u8,
     length: u8,
-    base_powers: &Vec<usize>,
 ) -> usize {
-    let mut basis_state = 0;
+    let mut value = value;
+    let mut digit_sum = 0;
+
+    for _ in 0..length {
+        digit_sum += value % base as usize;
+        value /= base as usize;
+    }
+
+    digit_sum
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hamiltonians::{Hamiltonian, Implemented, AFH, AKLT};
+    use crate::sector::Sector;
+    use crate::testing::{get_asymmetry, get_brute_force_spectrum, get_eigenvalues, get_model, get_sector_matrix};
+
+    /// Compares the spectrum of every sector of every
+    /// nonnegative S^z with the brute-force spectrum.
+    fn check_sectors<T: Implemented>(hamiltonian: &T, base: u8, length: u8) {
+        let twice_max_s_z = (base as i32 - 1) * length as i32;
+
+        for twice_s_z in (twice_max_s_z % 2..=twice_max_s_z).step_by(2) {
+            let total_s_z = SzSector::from_twice(twice_s_z);
 
-    for index in 0..length {
-        let digit_index = ((translate + index) % length) as usize;
-        basis_state += digits[digit_index] as usize * base_powers[index as usize];
+            for reflection in [Reflection::Bond, Reflection::Site] {
+                let model = get_model(base, length, total_s_z, true, Some(reflection), true);
+                let parities = |used: bool| if used {vec![Parity::Even, Parity::Odd]} else {vec![Parity::Even]};
+
+                for time_reversal in parities(model.symmetries.time_reversal) {
+                    for reflection_parity in [Parity::Even, Parity::Odd] {
+                        for translation in [Momentum::Zero, Momentum::Pi] {
+                            let sector = match Sector::new(&model, time_reversal, reflection_parity, translation) {
+                                Err(Error::OddLengthMomentum(_)) if length % 2 == 1 && translation == Momentum::Pi => continue,
+                                result => result,
+                            };
+                            let expected = get_brute_force_spectrum(
+                                hamiltonian,
+                                base,
+                                length,
+                                total_s_z,
+                                (model.symmetries.time_reversal.then_some(time_reversal), Some((reflection, reflection_parity)), Some(translation)),
+                            );
+                            let sector = match sector {
+                                Ok(sector) => sector,
+                                Err(Error::EmptySector) => {
+                                    assert!(expected.is_empty());
+                                    continue;
+                                },
+                                Err(error) => panic!("{}", error),
+                            };
+                            let matrix = get_sector_matrix(hamiltonian, &sector);
+                            let description = format!(
+                                "base {} length {} 2S^z {} {:?} {:?} {:?} {:?}",
+                                base, length, twice_s_z, reflection, time_reversal, reflection_parity, translation,
+                            );
+
+                            assert!(get_asymmetry(&matrix) < 1e-5, "{}: not Hermitian", description);
+
+                            let found = get_eigenvalues(&matrix);
+                            assert_eq!(found.len(), expected.len(), "{}: dimension", description);
+
+                            for (found, expected) in found.iter().zip(expected.iter()) {
+                                assert!((found - expected).abs() < 1e-4, "{}: {} != {}", description, found, expected);
+                            }
+                        }
+                    }
+                }
+            }
+        }
     }
 
-    basis_state
+    #[test]
+    fn sector_spectra_agree_with_brute_force() {
+        for length in 4..=9 {
+            check_sectors(&Hamiltonian::<AFH>::new(0.7), 2, length);
+        }
+
+        for length in 4..=6 {
+            check_sectors(&Hamiltonian::<AFH>::new(0.7), 3, length);
+            check_sectors(&Hamiltonian::<AKLT>::new(0.6), 3, length);
+        }
+    }
+
+    #[test]
+    fn momentum_pi_requires_an_even_length() {
+        for (base, length) in [(2, 7), (3, 5)] {
+            let model = get_model(base, length, SzSector::from_twice(((base as i32 - 1) * length as i32) % 2), false, None, true);
+
+            assert_eq!(
+                Sector::new(&model, Parity::Even, Parity::Even, Momentum::Pi).err(),
+                Some(Error::OddLengthMomentum(length)),
+            );
+            assert!(Sector::new(&model, Parity::Even, Parity::Even, Momentum::Zero).is_ok());
+        }
+
+        let model = get_model(3, 5, SzSector::new(1), false, None, false);
+
+        assert!(Sector::new(&model, Parity::Even, Parity::Even, Momentum::Pi).is_ok());
+    }
+
+    #[test]
+    fn permutations_of_empty_chains_are_rejected() {
+        assert_eq!(translate(0, 3, 0), Err(Error::InvalidLength(0)));
+        assert_eq!(reflect(0, 3, 0, Reflection::Site), Err(Error::InvalidLength(0)));
+        assert_eq!(reflect(0, 0, 4, Reflection::Bond), Err(Error::InvalidBase(0)));
+        assert_eq!(translate(5, 3, 2), Ok(7));
+    }
 }
\ No newline at end of file
@@ -0,0 +1,367 @@
+//! This module contains the helpers of the tests, which
+//! diagonalize the Hamiltonians of small chains by brute
+//! force in double precision.
+use crate::basis::SzSector;
+use crate::error::Error;
+use crate::hamiltonians::{apply_matrix_elements, Implemented, MatrixElements};
+use crate::model::{Model, ModelBuilder};
+use crate::sector::Sector;
+use crate::states::State;
+use crate::symmetries::{reflect, time_reverse, translate, Momentum, Parity, Reflection};
+
+/// Builds the matrix of a Hamiltonian in a [`Sector`],
+/// using the orthonormal basis of the representers with
+/// nonvanishing symmetry factors. The matrix is built by
+/// applying the Hamiltonian, so it is only symmetric if
+/// the [`Sector`] is Hermitian.
+pub fn get_sector_matrix<T: Implemented>(hamiltonian: &T, sector: &Sector) -> Vec<Vec<f64>> {
+    let symmetry_factors = &sector.symmetry_factors;
+    let indices: Vec<usize> = (0..sector.get_length())
+        .filter(|index| symmetry_factors.factors[*index] != 0.0)
+        .collect();
+    // The squared norm of the basis state of a
+    // representer is |G|/F.
+    let norms_squared: Vec<f64> = indices
+        .iter()
+        .map(|index| symmetry_factors.group_order as f64 / symmetry_factors.factors[*index] as f64)
+        .collect();
+    let mut matrix = vec![vec![0.0; indices.len()]; indices.len()];
+
+    for (column, column_index) in indices.iter().enumerate() {
+        let mut input_state = sector.from_zeros();
+        input_state.coefficients[*column_index] = 1.0;
+        let mut output_state = sector.from_zeros();
+        hamiltonian.apply(&input_state, &mut output_state, sector).unwrap();
+
+        for (row, row_index) in indices.iter().enumerate() {
+            matrix[row][column] = output_state.coefficients[*row_index] as f64
+                * (norms_squared[row] / norms_squared[column]).sqrt();
+        }
+    }
+
+    matrix
+}
+
+/// Gets the largest deviation of a matrix from its
+/// transpose.
+pub fn get_asymmetry(matrix: &[Vec<f64>]) -> f64 {
+    let mut deviation: f64 = 0.0;
+
+    for (row, elements) in matrix.iter().enumerate() {
+        for (column, element) in elements.iter().enumerate() {
+            deviation = deviation.max((element - matrix[column][row]).abs());
+        }
+    }
+
+    deviation
+}
+
+/// Gets the eigenvalues of a symmetric matrix in
+/// increasing order by the cyclic Jacobi method.
+pub fn get_eigenvalues(matrix: &[Vec<f64>]) -> Vec<f64> {
+    get_eigenpairs(matrix).into_iter().map(|(eigenvalue, _)| eigenvalue).collect()
+}
+
+/// Gets the eigenpairs of a symmetric matrix in
+/// increasing order of the eigenvalues by the cyclic
+/// Jacobi method. The eigenvectors are normalized.
+pub fn get_eigenpairs(matrix: &[Vec<f64>]) -> Vec<(f64, Vec<f64>)> {
+    let dimension = matrix.len();
+    let mut matrix = matrix.to_vec();
+    // The product of the rotations, whose columns become
+    // the eigenvectors.
+    let mut rotation: Vec<Vec<f64>> = (0..dimension)
+        .map(|row| (0..dimension).map(|column| if row == column {1.0} else {0.0}).collect())
+        .collect();
+
+    for _ in 0..100 {
+        let off_diagonal_norm: f64 = (0..dimension)
+            .flat_map(|row| (0..dimension).filter(move |column| *column != row).map(move |column| (row, column)))
+            .map(|(row, column)| matrix[row][column] * matrix[row][column])
+            .sum();
+
+        if off_diagonal_norm < 1e-24 {
+            break;
+        }
+
+        for p in 0..dimension {
+            for q in p + 1..dimension {
+                if matrix[p][q].abs() < 1e-300 {
+                    continue;
+                }
+
+                let theta = (matrix[q][q] - matrix[p][p]) / (2.0 * matrix[p][q]);
+                let t = theta.signum() / (theta.abs() + (theta * theta + 1.0).sqrt());
+                let t = if theta == 0.0 {1.0} else {t};
+                let c = 1.0 / (t * t + 1.0).sqrt();
+                let s = t * c;
+
+                for row in matrix.iter_mut().chain(rotation.iter_mut()) {
+                    let (a_p, a_q) = (row[p], row[q]);
+                    row[p] = c * a_p - s * a_q;
+                    row[q] = s * a_p + c * a_q;
+                }
+
+                let (lower_rows, upper_rows) = matrix.split_at_mut(q);
+
+                for (a_p, a_q) in lower_rows[p].iter_mut().zip(upper_rows[0].iter_mut()) {
+                    (*a_p, *a_q) = (c * *a_p - s * *a_q, s * *a_p + c * *a_q);
+                }
+            }
+        }
+    }
+
+    let mut eigenpairs: Vec<(f64, Vec<f64>)> = (0..dimension)
+        .map(|index| (matrix[index][index], rotation.iter().map(|row| row[index]).collect()))
+        .collect();
+    eigenpairs.sort_by(|eigenpair_0, eigenpair_1| eigenpair_0.0.total_cmp(&eigenpair_1.0));
+
+    eigenpairs
+}
+
+/// Gets the spectrum of a Hamiltonian in a sector of a
+/// periodic chain by brute force: the Hamiltonian is
+/// built in the plain basis of the total quantum spin in
+/// the z-direction and restricted to the range of the
+/// projector (1/|G|) Σ_g χ(g) g, which is built from the
+/// permutations of the basis states.
+pub fn get_brute_force_spectrum<T: Implemented>(
+    hamiltonian: &T,
+    base: u8,
+    length: u8,
+    total_s_z: SzSector,
+    symmetries: (Option<Parity>, Option<(Reflection, Parity)>, Option<Momentum>),
+) -> Vec<f64> {
+    let (time_reversal, reflection, translation) = symmetries;
+    let model = Model::new_plain(base, length, Some(total_s_z)).unwrap();
+    let sector = Sector::trivial(&model).unwrap();
+    let matrix = get_sector_matrix(hamiltonian, &sector);
+    let dimension = matrix.len();
+    let sign = |parity: Option<Parity>| if parity == Some(Parity::Odd) {-1.0f64} else {1.0};
+    let time_reversals = if time_reversal.is_some() {2} else {1};
+    let reflections = if reflection.is_some() {2} else {1};
+    let translations = if translation.is_some() {length as i32} else {1};
+    let momentum_sign: f64 = if translation == Some(Momentum::Pi) {-1.0} else {1.0};
+    let group_order = (time_reversals * reflections * translations) as f64;
+    let mut projector = vec![vec![0.0; dimension]; dimension];
+
+    for (column, value) in model.basis_states.representers.iter().enumerate() {
+        for time_reversed in 0..time_reversals {
+            for reflected in 0..reflections {
+                for translated in 0..translations {
+                    let mut image = *value;
+
+                    if time_reversed == 1 {
+                        image = time_reverse(image, base, length);
+                    }
+
+                    if let (1, Some((reflection, _))) = (reflected, reflection) {
+                        image = reflect(image, base, length, reflection).unwrap();
+                    }
+
+                    for _ in 0..translated {
+                        image = translate(image, base, length).unwrap();
+                    }
+
+                    let character = sign(time_reversal).powi(time_reversed)
+                        * sign(reflection.map(|(_, parity)| parity)).powi(reflected)
+                        * momentum_sign.powi(translated);
+                    let row = model.basis_states.get_index(image).unwrap();
+                    projector[row][column] += character / group_order;
+                }
+            }
+        }
+    }
+
+    // An orthonormal basis of the range of the projector
+    // by the Gram-Schmidt process.
+    let mut basis: Vec<Vec<f64>> = Vec::new();
+
+    for column in 0..dimension {
+        let mut vector: Vec<f64> = projector.iter().map(|row| row[column]).collect();
+
+        for basis_vector in basis.iter() {
+            let overlap: f64 = basis_vector.iter().zip(vector.iter()).map(|(a, b)| a * b).sum();
+            vector.iter_mut().zip(basis_vector.iter()).for_each(|(element, basis_element)| *element -= overlap * basis_element);
+        }
+
+        let norm = vector.iter().map(|element| element * element).sum::<f64>().sqrt();
+
+        if norm > 1e-8 {
+            basis.push(vector.iter().map(|element| element / norm).collect());
+        }
+    }
+
+    let products: Vec<Vec<f64>> = basis
+        .iter()
+        .map(|vector| matrix.iter().map(|row| row.iter().zip(vector.iter()).map(|(a, b)| a * b).sum()).collect())
+        .collect();
+    let restricted_matrix: Vec<Vec<f64>> = basis
+        .iter()
+        .map(|row_vector| {
+            products
+                .iter()
+                .map(|product| row_vector.iter().zip(product.iter()).map(|(a, b)| a * b).sum())
+                .collect()
+        })
+        .collect();
+
+    get_eigenvalues(&restricted_matrix)
+}
+
+/// Builds the [`Model`] of a periodic chain with the
+/// given symmetries.
+pub fn get_model(
+    base: u8,
+    length: u8,
+    total_s_z: SzSector,
+    time_reversal: bool,
+    reflection: Option<Reflection>,
+    translation: bool,
+) -> Model {
+    ModelBuilder::new(base, length)
+        .total_s_z(Some(total_s_z))
+        .time_reversal(time_reversal)
+        .reflection(reflection)
+        .translation(translation)
+        .build()
+        .unwrap()
+}
+
+/// A Hamiltonian plus the terms
+/// Σ_n h_n S^z_n + D_n (S^z_n)^2 + g_n S^x_n + J_n S_n·S_{n+1},
+/// whose coefficients depend on the site or the bond, so
+/// they can break the symmetries of the chain. Missing
+/// coefficients are 0.
+pub struct Perturbed<T> {
+    pub hamiltonian: T,
+    pub fields: Vec<f32>,
+    pub anisotropies: Vec<f32>,
+    pub transverse_fields: Vec<f32>,
+    pub exchanges: Vec<f32>,
+}
+
+impl<T> Perturbed<T> {
+    /// Creates a [`Perturbed`] Hamiltonian without terms.
+    pub fn new(hamiltonian: T) -> Self {
+        Self {
+            hamiltonian,
+            fields: Vec::new(),
+            anisotropies: Vec::new(),
+            transverse_fields: Vec::new(),
+            exchanges: Vec::new(),
+        }
+    }
+}
+
+/// Gets the matrix element ⟨d+1|S^+|d⟩ of a site.
+fn get_raising_element(digit: u8, spin: f32) -> f32 {
+    let m = digit as f32 - spin;
+
+    (spin * (spin + 1.0) - m * (m + 1.0)).max(0.0).sqrt()
+}
+
+impl<T: MatrixElements> MatrixElements for Perturbed<T> {
+    fn get_matrix_elements(
+        &self,
+        value: usize,
+        digits: &[u8],
+        model: &Model,
+        matrix_elements: &mut Vec<(usize, f32)>,
+    ) {
+        self.hamiltonian.get_matrix_elements(value, digits, model, matrix_elements);
+
+        let spin = model.spin;
+        let top_digit = model.base - 1;
+        let mut diagonal_element = 0.0;
+
+        for (site, digit) in digits[..model.length as usize].iter().copied().enumerate() {
+            let sigma = digit as f32 - spin;
+            let power = model.base_powers[site];
+            diagonal_element += self.fields.get(site).copied().unwrap_or(0.0) * sigma;
+            diagonal_element += self.anisotropies.get(site).copied().unwrap_or(0.0) * sigma * sigma;
+            let transverse_field = self.transverse_fields.get(site).copied().unwrap_or(0.0);
+
+            if transverse_field != 0.0 && digit < top_digit {
+                let element = 0.5 * transverse_field * get_raising_element(digit, spin);
+                matrix_elements.push((value + power, element));
+            }
+
+            if transverse_field != 0.0 && digit > 0 {
+                let element = 0.5 * transverse_field * get_raising_element(digit - 1, spin);
+                matrix_elements.push((value - power, element));
+            }
+        }
+
+        for bond in 0..model.bonds.min(self.exchanges.len()) {
+            let exchange = self.exchanges[bond];
+            let next_site = (bond + 1) % model.length as usize;
+            let (digit, next_digit) = (digits[bond], digits[next_site]);
+            let (power, next_power) = (model.base_powers[bond], model.base_powers[next_site]);
+            diagonal_element += exchange * (digit as f32 - spin) * (next_digit as f32 - spin);
+
+            // S^+_n S^-_{n+1} and S^-_n S^+_{n+1}.
+            if digit < top_digit && next_digit > 0 {
+                let element = 0.5 * exchange
+                    * get_raising_element(digit, spin)
+                    * get_raising_element(next_digit - 1, spin);
+                matrix_elements.push((value + power - next_power, element));
+            }
+
+            if digit > 0 && next_digit < top_digit {
+                let element = 0.5 * exchange
+                    * get_raising_element(digit - 1, spin)
+                    * get_raising_element(next_digit, spin);
+                matrix_elements.push((value - power + next_power, element));
+            }
+        }
+
+        matrix_elements.push((value, diagonal_element));
+    }
+}
+
+impl<T: MatrixElements> Implemented for Perturbed<T> {
+    fn apply(
+        &self,
+        input_state: &State,
+        output_state: &mut State,
+        sector: &Sector,
+    ) -> Result<(), Error> {
+        apply_matrix_elements(self, input_state, output_state, sector)
+    }
+
+    fn get_max_eigenenergy(&self, model: &Model) -> f32 {
+        let spin = model.spin;
+        let sum = |coefficients: &[f32]| coefficients.iter().map(|coefficient| coefficient.abs()).sum::<f32>();
+
+        self.hamiltonian.get_max_eigenenergy(model)
+            + sum(&self.fields) * spin
+            + sum(&self.anisotropies) * spin * spin
+            + sum(&self.transverse_fields) * spin
+            + sum(&self.exchanges) * spin * (spin + 1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hamiltonians::{Hamiltonian, AFH};
+
+    #[test]
+    fn perturbed_exchanges_agree_with_afh() {
+        for (base, length) in [(2, 6), (3, 5)] {
+            let model = Model::new_plain(base, length, None).unwrap();
+            let sector = Sector::trivial(&model).unwrap();
+            let mut perturbed = Perturbed::new(Hamiltonian::<AFH>::new(0.0));
+            perturbed.exchanges = vec![1.0; length as usize];
+            let found = get_sector_matrix(&perturbed, &sector);
+            let expected = get_sector_matrix(&Hamiltonian::<AFH>::new(0.5), &sector);
+
+            for (found_row, expected_row) in found.iter().zip(expected.iter()) {
+                for (found, expected) in found_row.iter().zip(expected_row.iter()) {
+                    assert!((found - 2.0 * expected).abs() < 1e-5);
+                }
+            }
+        }
+    }
+}
\ No newline at end of file
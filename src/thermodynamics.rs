@@ -0,0 +1,270 @@
+//! This module contains the finite-temperature Lanczos
+//! method (FTLM), which estimates the trace of e^{-H/T}
+//! in every sector by Lanczos recursions starting from
+//! random [`crate::states::State`]s. The sectors are
+//! those of the total quantum spin in the z-direction,
+//! time reversal and reflection, which together span
+//! the full Hilbert space. Translation is not used, since
+//! only the momenta 0 and π are available.
+use crate::basis::SzSector;
+use crate::error::Error;
+use crate::evolution::ComplexState;
+use crate::hamiltonians::Implemented;
+use crate::lanczos::get_lanczos_coefficients;
+use crate::model::ModelBuilder;
+use crate::sector::Sector;
+use crate::states::State;
+use crate::symmetries::{Momentum, Parity};
+
+/// The thermodynamic quantities per site at one
+/// temperature, in units where k_B = 1 and the magnetic
+/// moment is S^z.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct ThermodynamicPoint {
+    pub temperature: f32,
+    pub energy: f32,
+    pub specific_heat: f32,
+    pub entropy: f32,
+    /// The uniform susceptibility ⟨(S^z_tot)^2⟩/(TL).
+    pub susceptibility: f32,
+}
+
+/// An eigenvalue of a Lanczos recursion with its weight
+/// in the trace.
+struct Level {
+    energy: f64,
+    weight: f64,
+    s_z: f64,
+}
+
+/// Gets the thermodynamic quantities per site at every
+/// temperature. A [`crate::model::Model`] is built by
+/// `model_builder` for every sector from the smallest
+/// nonnegative S^z to S*length, and the sectors with
+/// opposite S^z are counted by weight 2, so the
+/// Hamiltonian must commute with time reversal. In a
+/// sector of dimension D, the trace is estimated from
+/// R = `samples` random normalized states |r⟩ as
+/// D/R Σ_r ⟨r|e^{-H/T}|r⟩. Their amplitudes in the
+/// orthonormal basis are uniformly random. Every sample
+/// uses `lanczos_steps` steps of the Lanczos recursion.
+/// Sectors of dimension at most `samples` are traced
+/// exactly over their basis states.
+/// The statistical errors are largest at temperatures
+/// below the lowest excitation energies, where few
+/// states contribute.
+pub fn get_thermodynamics<T: Implemented>(
+    hamiltonian: &T,
+    model_builder: ModelBuilder,
+    samples: usize,
+    lanczos_steps: usize,
+    temperatures: &[f32],
+) -> Result<Vec<ThermodynamicPoint>, Error> {
+    let base = model_builder.base;
+    let length = model_builder.length;
+    let twice_max_s_z = (base as i32 - 1) * length as i32;
+    let mut levels = Vec::new();
+
+    for twice_s_z in (twice_max_s_z % 2..=twice_max_s_z).step_by(2) {
+        let model = model_builder
+            .total_s_z(Some(SzSector::from_twice(twice_s_z)))
+            .translation(false)
+            .build()?;
+        let degeneracy = if twice_s_z == 0 {1.0} else {2.0};
+        let parities = |used: bool| if used {&[Parity::Even, Parity::Odd][..]} else {&[Parity::Even][..]};
+
+        for time_reversal in parities(model.symmetries.time_reversal).iter().copied() {
+            for reflection in parities(model.symmetries.reflection.is_some()).iter().copied() {
+                let sector = match Sector::new(&model, time_reversal, reflection, Momentum::Zero) {
+                    Ok(sector) => sector,
+                    Err(Error::EmptySector) => continue,
+                    Err(error) => return Err(error),
+                };
+                let basis_state_indices: Vec<usize> = sector.symmetry_factors.factors
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, symmetry_factor)| **symmetry_factor != 0.0)
+                    .map(|(basis_state_index, _)| basis_state_index)
+                    .collect();
+                let dimension = basis_state_indices.len();
+                let exact = dimension <= samples;
+                let sample_weight = if exact {1.0} else {dimension as f64 / samples as f64};
+                // The squared norm of the basis state of a
+                // representer r is |G|/F_r, so the coefficients
+                // are scaled by sqrt(F_r/|G|) for the random
+                // amplitudes to be those of the orthonormal basis.
+                let scales: Vec<f32> = sector.symmetry_factors.factors
+                    .iter()
+                    .map(|symmetry_factor| (symmetry_factor / sector.symmetry_factors.group_order as f32).sqrt())
+                    .collect();
+                let initial_states: Box<dyn Iterator<Item = State>> = if exact {
+                    Box::new(basis_state_indices.iter().map(|basis_state_index| {
+                        let mut initial_state = sector.from_zeros();
+                        initial_state.coefficients[*basis_state_index] = 1.0;
+                        initial_state
+                    }))
+                } else {
+                    Box::new((0..samples).map(|sample| {
+                        let mut initial_state = sector.from_random(model.seed.wrapping_add(sample as u64));
+                        initial_state.coefficients
+                            .iter_mut()
+                            .zip(scales.iter())
+                            .for_each(|(coefficient, scale)| *coefficient *= scale);
+                        initial_state
+                    }))
+                };
+
+                for mut initial_state in initial_states {
+                    initial_state.normalize(&sector)?;
+
+                    let initial_state = ComplexState::from_real(initial_state, &sector)?;
+                    let tridiagonal = get_lanczos_coefficients(hamiltonian, &initial_state, &sector, lanczos_steps)?;
+                    let (eigenvalues, eigenvectors) = tridiagonal.get_eigenpairs()?;
+
+                    for (eigenvalue, eigenvector) in eigenvalues.iter().zip(eigenvectors.iter()) {
+                        levels.push(Level {
+                            energy: *eigenvalue,
+                            weight: degeneracy * sample_weight * eigenvector[0] * eigenvector[0],
+                            s_z: twice_s_z as f64 / 2.0,
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    // The energies are measured from the lowest one, so
+    // the Boltzmann factors do not overflow.
+    let min_energy = levels.iter().map(|level| level.energy).fold(f64::INFINITY, f64::min);
+
+    Ok(temperatures
+        .iter()
+        .map(|temperature| {
+            let beta = 1.0 / *temperature as f64;
+            let mut partition_function = 0.0;
+            let mut energy = 0.0;
+            let mut energy_squared = 0.0;
+            let mut s_z_squared = 0.0;
+
+            for level in levels.iter() {
+                let relative_energy = level.energy - min_energy;
+                let boltzmann_weight = level.weight * (-beta * relative_energy).exp();
+                partition_function += boltzmann_weight;
+                energy += boltzmann_weight * relative_energy;
+                energy_squared += boltzmann_weight * relative_energy * relative_energy;
+                s_z_squared += boltzmann_weight * level.s_z * level.s_z;
+            }
+
+            energy /= partition_function;
+            energy_squared /= partition_function;
+            s_z_squared /= partition_function;
+
+            ThermodynamicPoint {
+                temperature: *temperature,
+                energy: ((energy + min_energy) / length as f64) as f32,
+                specific_heat: (beta * beta * (energy_squared - energy * energy) / length as f64) as f32,
+                entropy: ((partition_function.ln() + beta * energy) / length as f64) as f32,
+                susceptibility: (beta * s_z_squared / length as f64) as f32,
+            }
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hamiltonians::{apply_matrix_elements, MatrixElements};
+    use crate::model::Model;
+    use crate::symmetries::{reflect, Reflection};
+
+    /// The single-ion anisotropy Σ_n (S^z_n)^2.
+    struct Anisotropy;
+
+    impl MatrixElements for Anisotropy {
+        fn get_matrix_elements(
+            &self,
+            value: usize,
+            digits: &[u8],
+            model: &Model,
+            matrix_elements: &mut Vec<(usize, f32)>,
+        ) {
+            let energy: f32 = digits[..model.length as usize].iter().map(|digit| (*digit as f32 - model.spin).powi(2)).sum();
+            matrix_elements.push((value, energy));
+        }
+    }
+
+    impl Implemented for Anisotropy {
+        fn apply(
+            &self,
+            input_state: &State,
+            output_state: &mut State,
+            sector: &Sector,
+        ) -> Result<(), Error> {
+            apply_matrix_elements(self, input_state, output_state, sector)
+        }
+
+        fn get_max_eigenenergy(&self, model: &Model) -> f32 {
+            model.spin * model.spin * model.length as f32
+        }
+    }
+
+    /// The Hamiltonian L P, where P projects onto the
+    /// basis states that are invariant under site-centered
+    /// reflection. These are the basis states of a sector
+    /// whose symmetry factors differ from the others.
+    struct ReflectionInvariance;
+
+    impl MatrixElements for ReflectionInvariance {
+        fn get_matrix_elements(
+            &self,
+            value: usize,
+            _digits: &[u8],
+            model: &Model,
+            matrix_elements: &mut Vec<(usize, f32)>,
+        ) {
+            if reflect(value, model.base, model.length, Reflection::Site) == Ok(value) {
+                matrix_elements.push((value, model.length as f32));
+            }
+        }
+    }
+
+    impl Implemented for ReflectionInvariance {
+        fn apply(
+            &self,
+            input_state: &State,
+            output_state: &mut State,
+            sector: &Sector,
+        ) -> Result<(), Error> {
+            apply_matrix_elements(self, input_state, output_state, sector)
+        }
+
+        fn get_max_eigenenergy(&self, model: &Model) -> f32 {
+            model.length as f32
+        }
+    }
+
+    #[test]
+    fn infinite_temperature_limits_are_recovered() {
+        // At S=1, ⟨(S^z)^2⟩ = 2/3 and the entropy per site
+        // is ln 3 at infinite temperature.
+        let model_builder = ModelBuilder::new(3, 8)
+            .time_reversal(true)
+            .reflection(Some(Reflection::Site));
+        let points = get_thermodynamics(&Anisotropy, model_builder, 20, 10, &[1e5]).unwrap();
+
+        assert!((points[0].energy - 2.0 / 3.0).abs() < 5e-3, "{:?}", points[0]);
+        assert!((points[0].entropy - 3f32.ln()).abs() < 5e-3, "{:?}", points[0]);
+    }
+
+    #[test]
+    fn random_states_are_uniform_in_the_orthonormal_basis() {
+        // At S=1 and length 8, 3^5 of the 3^8 basis states
+        // are invariant under site-centered reflection.
+        let model_builder = ModelBuilder::new(3, 8)
+            .time_reversal(true)
+            .reflection(Some(Reflection::Site));
+        let points = get_thermodynamics(&ReflectionInvariance, model_builder, 20, 10, &[1e5]).unwrap();
+
+        assert!((points[0].energy - 1.0 / 27.0).abs() < 5e-3, "{:?}", points[0]);
+    }
+}
\ No newline at end of file